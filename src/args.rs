@@ -1,18 +1,25 @@
 use clap::Parser;
 use log::{debug, info};
 
+use tgif::error::TgifError;
+
 #[derive(Parser, Debug)]
 #[clap(name = "TGIF")]
 #[clap(about = "Encodes and decodes grayscale images from/into the Turbo Gray Image Format")]
 #[clap(author, version, about, long_about = None)]
 pub struct Cli {
-    /// Input image (eg: TGIF, PNG, ...)
-    #[clap(value_parser)]
-    pub src: camino::Utf8PathBuf,
+    /// Preview a TGIF file directly in the terminal instead of converting it. When omitted,
+    /// `src`/`dst` below are used to convert between TGIF and an ordinary image format.
+    #[clap(subcommand)]
+    pub command: Option<Command>,
 
-    /// Output image (eg: TGIF, PNG, ...)
-    #[clap(value_parser)]
-    pub dst: camino::Utf8PathBuf,
+    /// Input image (eg: TGIF, PNG, ...). Unused when a subcommand is given.
+    #[clap(value_parser, required_unless_present = "command")]
+    pub src: Option<camino::Utf8PathBuf>,
+
+    /// Output image (eg: TGIF, PNG, ...). Unused when a subcommand is given.
+    #[clap(value_parser, required_unless_present = "command")]
+    pub dst: Option<camino::Utf8PathBuf>,
 
     /// Number of bits used to encode the remainder. Should be 0..=7. [Default: 2]
     #[clap(short, long)]
@@ -21,19 +28,87 @@ pub struct Cli {
     /// Size of self contained chunk in Kibibyte. Should be equal to L1 cache size. [Default: 128]
     #[clap(short, long)]
     pub chunk_size: Option<u32>,
+
+    /// Recover undamaged chunks instead of aborting on the first CRC32 mismatch. Only used
+    /// when decoding TGIF.
+    #[clap(long)]
+    pub skip_crc_errors: bool,
+
+    /// Use a hybrid run-length/rice encoding that collapses runs of identical consecutive
+    /// rice indices (eg flat regions). Only used when encoding an 8-bit image into TGIF.
+    #[clap(long)]
+    pub rle: bool,
+
+    /// Pick the Rice remainder width per self-contained chunk instead of a single value for
+    /// the whole image, trading a small per-chunk header for better compression when texture
+    /// varies across the image. Overrides `--rem-bits`. Only used when encoding an 8-bit
+    /// image into TGIF.
+    #[clap(long)]
+    pub adaptive: bool,
+
+    /// Run RGB(A) color channels through the reversible YCoCg-R transform before prediction,
+    /// which usually decorrelates them enough to compress noticeably better. Ignored for
+    /// grayscale images. Only used when encoding an RGB/RGBA image into TGIF.
+    #[clap(long)]
+    pub color_transform: bool,
+
+    /// Split each plane into `2^p` partitions and pick the Rice parameter per partition
+    /// (FLAC-style), instead of one `rem_bits` for the whole `CHUNK_SIZE` block. Typically
+    /// wins a further 5-15% over `--adaptive` on images whose local activity varies, at the
+    /// cost of decoding the whole plane as a single unit instead of in parallel `CHUNK_SIZE`
+    /// blocks. Overrides `--rem-bits`/`--adaptive`/`--rle`. Only used when encoding an 8-bit
+    /// image into TGIF.
+    #[clap(long)]
+    pub rice_partition: bool,
+
+    /// Choose the spatial predictor independently per scanline (PNG-style) instead of once for
+    /// the whole plane. Typically compresses better on images whose local structure varies row
+    /// to row, at the cost of a small uncompressed per-row tag section. Only used when encoding
+    /// an 8-bit image into TGIF.
+    #[clap(long)]
+    pub per_scanline_predictor: bool,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum Command {
+    /// Render a decoded TGIF image in the terminal using ANSI truecolor half-blocks
+    View(ViewCommand),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ViewCommand {
+    /// Path to the TGIF file to preview
+    #[clap(value_parser)]
+    pub path: camino::Utf8PathBuf,
+
+    /// Downscale the preview to fit this many terminal columns. [Default: the image's width]
+    #[clap(short, long)]
+    pub width: Option<u32>,
 }
 
 impl Cli {
-    pub fn verify_arguments(self) -> Operation {
-        match (&self.src.extension(), &self.dst.extension()) {
+    pub fn verify_arguments(self) -> Result<Operation, TgifError> {
+        if let Some(Command::View(view)) = self.command {
+            return Ok(Operation::View(View {
+                path: view.path,
+                width: view.width,
+            }));
+        }
+
+        // Guaranteed `Some` by `required_unless_present = "command"` once no subcommand was given.
+        let src = self.src.expect("src is required when no subcommand is given");
+        let dst = self.dst.expect("dst is required when no subcommand is given");
+
+        match (&src.extension(), &dst.extension()) {
             (Some("tgif"), Some(x)) if x != &"tgif" => {
                 if self.rem_bits.is_some() || self.chunk_size.is_some() {
                     info!("The provided CLI arguments are not being used when decoding TGIF")
                 }
-                Operation::FromTGIF(FromTGIF {
-                    src: self.src,
-                    dst: self.dst,
-                })
+                Ok(Operation::FromTGIF(FromTGIF {
+                    src,
+                    dst,
+                    skip_crc_errors: self.skip_crc_errors,
+                }))
             }
 
             (Some(x), Some("tgif")) if x != &"tgif" => {
@@ -53,20 +128,32 @@ impl Cli {
                     }
                 };
 
-                assert!(
-                    rem_bits < 8,
-                    "The number of remainder bits should be lower than 8"
-                );
-                assert_ne!(chunk_size, 0, "The chunk size must be higher than 0");
+                if rem_bits >= 8 {
+                    return Err(TgifError::InvalidRemBits(rem_bits));
+                }
+                if chunk_size == 0 {
+                    return Err(TgifError::InvalidChunkSize);
+                }
+                if self.adaptive && self.rem_bits.is_some() {
+                    info!("--rem-bits is ignored when --adaptive is set");
+                }
+                if self.rice_partition && self.rem_bits.is_some() {
+                    info!("--rem-bits is ignored when --rice-partition is set");
+                }
 
-                Operation::ToTGIF(ToTGIF {
-                    src: self.src,
-                    dst: self.dst,
+                Ok(Operation::ToTGIF(ToTGIF {
+                    src,
+                    dst,
                     rem_bits,
                     chunk_size: chunk_size * 1024 * 8, // Converting to Kibibyte
-                })
+                    rle: self.rle,
+                    adaptive: self.adaptive,
+                    color_transform: self.color_transform,
+                    rice_partition: self.rice_partition,
+                    per_scanline_predictor: self.per_scanline_predictor,
+                }))
             }
-            _ => panic!("Only converting to/from TGIF is supported"),
+            _ => Err(TgifError::UnsupportedConversion),
         }
     }
 }
@@ -75,6 +162,7 @@ impl Cli {
 pub enum Operation {
     ToTGIF(ToTGIF),
     FromTGIF(FromTGIF),
+    View(View),
 }
 
 #[derive(Debug)]
@@ -87,6 +175,20 @@ pub struct ToTGIF {
     pub rem_bits: u8,
     /// Number of Kibibytes that are used for the self contained chunk
     pub chunk_size: u32,
+    /// Whether to use the hybrid run-length/rice encoding (only applied to 8-bit images)
+    pub rle: bool,
+    /// Whether to pick the Rice remainder width per chunk instead of using `rem_bits` for the
+    /// whole image (only applied to 8-bit images)
+    pub adaptive: bool,
+    /// Whether to run RGB(A) color channels through the YCoCg-R transform before prediction
+    /// (only applied to RGB/RGBA images)
+    pub color_transform: bool,
+    /// Whether to use FLAC-style per-partition adaptive Rice parameters instead of one
+    /// `rem_bits` for the whole image (only applied to 8-bit images; overrides `rle`/`adaptive`)
+    pub rice_partition: bool,
+    /// Whether to choose the spatial predictor independently per scanline instead of once per
+    /// plane (only applied to 8-bit images)
+    pub per_scanline_predictor: bool,
 }
 
 #[derive(Debug)]
@@ -95,4 +197,14 @@ pub struct FromTGIF {
     pub src: camino::Utf8PathBuf,
     /// Path to TGIF destination file
     pub dst: camino::Utf8PathBuf,
+    /// Recover undamaged chunks instead of aborting on the first CRC32 mismatch
+    pub skip_crc_errors: bool,
+}
+
+#[derive(Debug)]
+pub struct View {
+    /// Path to the TGIF file to preview
+    pub path: camino::Utf8PathBuf,
+    /// Downscale the preview to fit this many terminal columns
+    pub width: Option<u32>,
 }
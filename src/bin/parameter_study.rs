@@ -58,11 +58,167 @@ struct Score {
     file_name: String,
     dataset: String,
     uncompressed: u64,
-    compressed: f64,
+    /// Percentage of `uncompressed` each entropy backend (see [`backends`]) would need, in
+    /// the same order `backends()` returns them.
+    backend_compressed: Vec<(&'static str, f64)>,
     max_png_compressed: f64,
     parameter: Parameter,
 }
 
+/// A pluggable entropy coding backend the parameter study scores `compress`'s encoded symbol
+/// stream against, so the study can compare Huffman coding (what `tgif` historically used
+/// here) against general-purpose alternatives on the exact same residual stream.
+trait Backend {
+    /// Short name used as the CSV column prefix.
+    fn name(&self) -> &'static str;
+    /// Returns the number of bits needed to encode `symbols`.
+    fn encoded_bits(&self, symbols: &[u32]) -> usize;
+}
+
+/// The backends compared for every parameter combination.
+fn backends() -> Vec<Box<dyn Backend>> {
+    vec![Box::new(Huffman), Box::new(Lzw), Box::new(PackBits)]
+}
+
+/// Canonical Huffman coding via `huffman_compress`, as this file always did.
+struct Huffman;
+
+impl Backend for Huffman {
+    fn name(&self) -> &'static str { "huffman" }
+
+    fn encoded_bits(&self, symbols: &[u32]) -> usize {
+        let freq = count_frequency(symbols.iter().copied());
+        let (book, _) = CodeBuilder::from_iter(freq).finish();
+        let mut compressed = bit_vec::BitVec::new();
+        for number in symbols {
+            book.encode(&mut compressed, number).unwrap();
+        }
+        compressed.len()
+    }
+}
+
+/// Classic LZW dictionary coding: codewords grow from `ceil(log2(alphabet size))` bits and
+/// widen by one bit every time the dictionary size crosses a power of two.
+struct Lzw;
+
+/// Number of bits needed to represent any value in `0..n`.
+fn bits_needed(n: u32) -> usize {
+    if n <= 1 { 1 } else { (32 - (n - 1).leading_zeros()) as usize }
+}
+
+impl Backend for Lzw {
+    fn name(&self) -> &'static str { "lzw" }
+
+    fn encoded_bits(&self, symbols: &[u32]) -> usize {
+        if symbols.is_empty() {
+            return 0;
+        }
+
+        let mut alphabet: Vec<u32> = symbols.to_vec();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+
+        let mut dict: HashMap<Vec<u32>, u32> = HashMap::new();
+        for (code, &symbol) in alphabet.iter().enumerate() {
+            dict.insert(vec![symbol], code as u32);
+        }
+        let mut next_code = alphabet.len() as u32;
+
+        let mut bits = 0usize;
+        let mut current: Vec<u32> = Vec::new();
+        for &symbol in symbols {
+            let mut extended = current.clone();
+            extended.push(symbol);
+            if dict.contains_key(&extended) {
+                current = extended;
+            } else {
+                bits += bits_needed(next_code);
+                dict.insert(extended, next_code);
+                next_code += 1;
+                current = vec![symbol];
+            }
+        }
+        if !current.is_empty() {
+            bits += bits_needed(next_code);
+        }
+
+        bits
+    }
+}
+
+/// Apple's PackBits run-length scheme, generalized from bytes to our `u32` symbol stream: a
+/// 1-byte control header precedes either a literal run (up to 128 symbols, each `symbol_bits`
+/// wide) or a repeat run (one value, repeated up to 128 times).
+struct PackBits;
+
+impl Backend for PackBits {
+    fn name(&self) -> &'static str { "packbits" }
+
+    fn encoded_bits(&self, symbols: &[u32]) -> usize {
+        if symbols.is_empty() {
+            return 0;
+        }
+        let max_symbol = *symbols.iter().max().unwrap();
+        let symbol_bits = bits_needed(max_symbol + 1);
+
+        const MAX_RUN: usize = 128;
+        let mut bits = 0usize;
+        let mut i = 0;
+        while i < symbols.len() {
+            let mut run_len = 1;
+            while run_len < MAX_RUN && i + run_len < symbols.len() && symbols[i + run_len] == symbols[i] {
+                run_len += 1;
+            }
+
+            if run_len >= 3 {
+                // Repeat run: header + a single repeated value
+                bits += 8 + symbol_bits;
+                i += run_len;
+            } else {
+                // Literal run: collect symbols until the next run of 3+ identical ones
+                let mut literal_len = 0;
+                while i < symbols.len() && literal_len < MAX_RUN {
+                    let mut peek_run = 1;
+                    while peek_run < MAX_RUN && i + peek_run < symbols.len() && symbols[i + peek_run] == symbols[i] {
+                        peek_run += 1;
+                    }
+                    if peek_run >= 3 {
+                        break;
+                    }
+                    i += 1;
+                    literal_len += 1;
+                }
+                bits += 8 + literal_len * symbol_bits;
+            }
+        }
+
+        bits
+    }
+}
+
+#[test]
+fn test_bits_needed() {
+    assert_eq!(bits_needed(1), 1);
+    assert_eq!(bits_needed(2), 1);
+    assert_eq!(bits_needed(3), 2);
+    assert_eq!(bits_needed(4), 2);
+    assert_eq!(bits_needed(5), 3);
+}
+
+#[test]
+fn test_packbits_prefers_repeat_runs_over_literals() {
+    let repeated = vec![7u32; 10];
+    let literal = (0..10u32).collect::<Vec<_>>();
+    assert!(PackBits.encoded_bits(&repeated) < PackBits.encoded_bits(&literal));
+}
+
+#[test]
+fn test_lzw_shrinks_on_repetitive_input() {
+    let repeated = vec![1u32, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2];
+    let random = vec![1u32, 9, 3, 7, 5, 2, 8, 4, 6, 0, 11, 10];
+    assert!(Lzw.encoded_bits(&repeated) < Lzw.encoded_bits(&random));
+}
+
 struct Image {
     path: PathBuf,
     size: u64,
@@ -174,12 +330,12 @@ fn compress(file: &Image, par: &Parameter) -> Score {
         }
     }
 
-    let freq = count_frequency(encoded.clone());
-    let (book, _) = CodeBuilder::from_iter(freq).finish();
-    let mut compressed = bit_vec::BitVec::new();
-    for number in encoded {
-        book.encode(&mut compressed, &number).unwrap();
-    }
+    let uncompressed_bits = (file.array.len() * 8) as f64;
+    let backend_compressed: Vec<(&'static str, f64)> = backends()
+        .iter()
+        .map(|backend| (backend.name(), 100.0 * backend.encoded_bits(&encoded) as f64 / uncompressed_bits))
+        .collect();
+
     let dataset = {
         if file.path.to_string_lossy().contains("/dark/") {
             "dark"
@@ -198,7 +354,7 @@ fn compress(file: &Image, par: &Parameter) -> Score {
         dataset: dataset.to_string(),
         parameter: par.clone(),
         uncompressed: (file.array.len() * 8) as u64,
-        compressed: 100.0 * (compressed.len() as f64 / (file.array.len() * 8) as f64),
+        backend_compressed,
         max_png_compressed: 100.0 * (file.size * 8) as f64 / (file.array.len() * 8) as f64,
     }
 }
@@ -230,7 +386,16 @@ fn main() {
         .append(true) // This is needed to append to file
         .open("results.csv")
         .unwrap();
-    file.write_all(b"INDEX,NAME,DATASET,UNCOMPRESSED,MAX_PNG_COMPRESSED,COMPRESSION,RLE,DELTA,RAW,DIRECTION\n").unwrap();
+    let backend_columns: Vec<String> = backends()
+        .iter()
+        .map(|backend| format!("{}_COMPRESSED", backend.name().to_uppercase()))
+        .collect();
+    file.write_all(
+        format!(
+            "INDEX,NAME,DATASET,UNCOMPRESSED,MAX_PNG_COMPRESSED,{},RLE,DELTA,RAW,DIRECTION\n",
+            backend_columns.join(","),
+        ).as_ref(),
+    ).unwrap();
 
     let mut index: u32 = 0;
     for path in images.iter() {
@@ -247,13 +412,18 @@ fn main() {
         };
         let scores: Vec<Score> = parameters.par_iter().map(|par| compress(&image, par)).collect();
         for score in scores {
+            let backend_values: Vec<String> = score
+                .backend_compressed
+                .iter()
+                .map(|(_, percent)| percent.to_string())
+                .collect();
             let out = format!("{},{},{},{},{},{},{},{},{},{:?}\n",
                               index,
                               score.file_name,
                               score.dataset,
                               score.uncompressed,
                               score.max_png_compressed,
-                              score.compressed,
+                              backend_values.join(","),
                               score.parameter.rle,
                               score.parameter.delta,
                               score.parameter.raw,
@@ -0,0 +1,138 @@
+//! A byte-packing bit writer, modeled on Parquet's auto-growing `BitWriter`.
+//!
+//! [`crate::to_tgif::encode`] and friends used to build the compressed stream as a `Vec<bool>`
+//! (one byte per *bit*, an 8x memory blow-up) and then fold it down into bytes in a second pass.
+//! `BitWriter` instead accumulates bits directly into a `u64` scratch register and flushes full
+//! bytes into a `Vec<u8>` as it fills, so the packed bytes exist from the start.
+
+pub(crate) struct BitWriter {
+    buf: Vec<u8>,
+    /// Bits not yet flushed to `buf`, right-aligned in the low `scratch_bits` bits.
+    scratch: u64,
+    scratch_bits: u32,
+}
+
+impl BitWriter {
+    pub(crate) fn with_capacity(bytes: usize) -> Self {
+        Self { buf: Vec::with_capacity(bytes), scratch: 0, scratch_bits: 0 }
+    }
+
+    /// Total number of bits written so far, flushed or not - used to find the current offset
+    /// within a chunk (see [`Self::align_to`]).
+    pub(crate) fn bit_len(&self) -> usize {
+        self.buf.len() * 8 + self.scratch_bits as usize
+    }
+
+    /// Writes the low `num_bits` bits of `value`, most-significant-bit first. `num_bits` may be
+    /// arbitrarily large (it's split internally), but `value` itself must fit in `num_bits`
+    /// bits for `num_bits <= 64`.
+    pub(crate) fn put_bits(&mut self, value: u64, num_bits: u32) {
+        if num_bits == 0 {
+            return;
+        }
+        if num_bits > 32 {
+            // Keeping each step within `scratch`'s safe range (<8 leftover bits + <=32 new bits
+            // always fits in a u64) rather than reasoning about overflow for the full width.
+            let hi_bits = num_bits - 32;
+            self.put_bits(value >> 32, hi_bits);
+            self.put_bits(value & 0xFFFF_FFFF, 32);
+            return;
+        }
+
+        debug_assert!(self.scratch_bits < 8);
+        let mask = (1u64 << num_bits) - 1;
+        self.scratch = (self.scratch << num_bits) | (value & mask);
+        self.scratch_bits += num_bits;
+
+        while self.scratch_bits >= 8 {
+            let shift = self.scratch_bits - 8;
+            self.buf.push((self.scratch >> shift) as u8);
+            self.scratch_bits -= 8;
+        }
+        self.scratch &= (1u64 << self.scratch_bits) - 1;
+    }
+
+    /// Unary-codes `quot`: `quot` "1" bits followed by a terminating "0" bit.
+    pub(crate) fn put_unary(&mut self, quot: u32) {
+        let mut remaining = quot;
+        while remaining >= 32 {
+            self.put_bits(u32::MAX as u64, 32);
+            remaining -= 32;
+        }
+        let width = remaining + 1;
+        let value = if remaining == 0 { 0 } else { ((1u64 << remaining) - 1) << 1 };
+        self.put_bits(value, width);
+    }
+
+    /// Pads with "1" bits up to the next multiple of `chunk_size` bits, if not already aligned.
+    /// Returns the number of padding bits written. Callers decide *when* to call this (eg
+    /// before a write that would otherwise straddle the boundary); `BitWriter` itself doesn't
+    /// track chunk size.
+    pub(crate) fn align_to(&mut self, chunk_size: usize) -> usize {
+        let rem = self.bit_len() % chunk_size;
+        if rem == 0 {
+            return 0;
+        }
+        let pad = chunk_size - rem;
+        let mut remaining = pad;
+        while remaining >= 32 {
+            self.put_bits(u32::MAX as u64, 32);
+            remaining -= 32;
+        }
+        if remaining > 0 {
+            self.put_bits((1u64 << remaining) - 1, remaining as u32);
+        }
+        pad
+    }
+
+    /// Flushes any remaining scratch bits (padding the final partial byte with "1"s, matching
+    /// the end-of-stream padding convention) and returns the packed bytes.
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        if self.scratch_bits > 0 {
+            let pad = 8 - self.scratch_bits;
+            self.put_bits((1u64 << pad) - 1, pad);
+        }
+        self.buf
+    }
+}
+
+#[test]
+fn test_put_bits_matches_manual_bool_packing() {
+    let mut writer = BitWriter::with_capacity(4);
+    writer.put_bits(0b101, 3);
+    writer.put_bits(0b1, 1);
+    writer.put_bits(0b0000, 4);
+    assert_eq!(writer.finish(), vec![0b1011_0000]);
+}
+
+#[test]
+fn test_put_unary_matches_manual_bool_packing() {
+    let mut writer = BitWriter::with_capacity(4);
+    writer.put_unary(3); // "1110"
+    writer.put_bits(0, 4); // pad out the byte
+    assert_eq!(writer.finish(), vec![0b1110_0000]);
+}
+
+#[test]
+fn test_align_to_pads_with_ones_to_chunk_boundary() {
+    let mut writer = BitWriter::with_capacity(4);
+    writer.put_bits(0b1, 1);
+    let padded = writer.align_to(8);
+    assert_eq!(padded, 7);
+    assert_eq!(writer.finish(), vec![0b1111_1111]);
+}
+
+#[test]
+fn test_align_to_is_noop_when_already_aligned() {
+    let mut writer = BitWriter::with_capacity(4);
+    writer.put_bits(0xAB, 8);
+    assert_eq!(writer.align_to(8), 0);
+    assert_eq!(writer.finish(), vec![0xAB]);
+}
+
+#[test]
+fn test_put_bits_handles_wide_values() {
+    let mut writer = BitWriter::with_capacity(8);
+    writer.put_bits(0xDEAD_BEEF_u64, 32);
+    assert_eq!(writer.finish(), vec![0xDE, 0xAD, 0xBE, 0xEF]);
+}
@@ -0,0 +1,360 @@
+//! The Rice+delta decode core shared by the parallel `std` CLI path and the
+//! slice-in/slice-out `no_std` entry point.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::constants::{
+    rev_rice_index_16, ADAPTIVE_REM_BITS, REV_RICE_INDEX, RICE_PARTITION_REM_BITS,
+    U8_TO_ARRAY_BOOL,
+};
+use crate::crc32::crc32;
+use crate::error::TgifError;
+use crate::header::Header;
+
+/// `Predictor::Left.tag()`, duplicated here as a raw byte so this `no_std` module doesn't have
+/// to depend on the `alloc`-gated `predictor` module just to check it.
+const LEFT_PREDICTOR_TAG: u8 = 1;
+
+/// Rejects headers that use an on-disk feature a decode entry point doesn't implement: a
+/// non-default predictor, per-chunk adaptive Rice parameters, rice-partitioned planes, more
+/// than one channel, or a bit depth other than 8 - always unsupported here, since doing
+/// otherwise would either silently mis-decode (wrong predictor, or a 16-bit sample read back as
+/// two 8-bit ones) or panic (shifting a `u8` by the `ADAPTIVE_REM_BITS` sentinel overflows).
+/// `allow_rle` lets callers that do implement the hybrid RLE encoding (see
+/// [`crate::decoder::Decoder`]) opt back into it; [`decode_into`], which doesn't, passes
+/// `false`. Callers that need these features should use the `std`-only parallel decode in
+/// `from_tgif::decode`/`decode_planes`/`decode_16` instead.
+pub(crate) fn check_supported(header: &Header, allow_rle: bool) -> Result<(), TgifError> {
+    let uses_unsupported_predictor = header.predictors[0] != LEFT_PREDICTOR_TAG;
+    let uses_adaptive = header.rem_bits == ADAPTIVE_REM_BITS;
+    let uses_rice_partition = header.rem_bits == RICE_PARTITION_REM_BITS;
+    let uses_unsupported_rle = header.rle && !allow_rle;
+    let uses_unsupported_bit_depth = header.bit_depth != 8;
+    if header.channels != 1
+        || uses_unsupported_rle
+        || uses_adaptive
+        || uses_rice_partition
+        || uses_unsupported_predictor
+        || uses_unsupported_bit_depth
+    {
+        return Err(TgifError::UnsupportedFeature);
+    }
+    Ok(())
+}
+
+/// Decodes `comp` (the CRC-prefixed chunks following the header) into `out`, which must already be sized to
+/// `header.width * header.height` bytes. This is the `no_std`/no-allocator
+/// entry point: embedded callers supply their own scratch buffer instead of
+/// letting the codec allocate one.
+pub fn decode_into(comp: &[u8], header: &Header, out: &mut [u8]) -> Result<(), TgifError> {
+    if header.width == 0 {
+        return Err(TgifError::BadHeader);
+    }
+    check_supported(header, false)?;
+    let expected = header.width as usize * header.height as usize;
+    if out.len() != expected {
+        return Err(TgifError::BadHeader);
+    }
+
+    // Each chunk is a 4-byte CRC32 followed by a self-contained (end-padded with "1"s)
+    // `header.chunk_size`-bit block, so it must be verified and decoded independently rather
+    // than as one continuous bitstream.
+    let mut cursor = 0usize;
+    for (chunk_index, raw) in comp.chunks(4 + header.chunk_size as usize / 8).enumerate() {
+        if raw.len() < 4 {
+            return Err(TgifError::UnexpectedEof);
+        }
+        let (crc_bytes, chunk) = raw.split_at(4);
+        let stored = u32::from_be_bytes([crc_bytes[0], crc_bytes[1], crc_bytes[2], crc_bytes[3]]);
+        let computed = crc32(chunk);
+        if stored != computed {
+            return Err(TgifError::CrcMismatch { chunk_index, stored, computed });
+        }
+        if header.rem_bits == 0 {
+            decode_without_remainder_into(chunk, out, &mut cursor);
+        } else {
+            decode_with_remainder_into(chunk, out, header.rem_bits, &mut cursor);
+        }
+    }
+    if cursor != expected {
+        return Err(TgifError::UnexpectedEof);
+    }
+
+    reverse_rice_and_delta(out, header.width as usize);
+
+    Ok(())
+}
+
+/// Same as [`decode_into`] but allocates and returns the result.
+#[cfg(feature = "alloc")]
+pub fn decode(comp: &[u8], header: &Header) -> Result<Vec<u8>, TgifError> {
+    if header.width == 0 {
+        return Err(TgifError::BadHeader);
+    }
+    let mut out = alloc::vec![0u8; header.width as usize * header.height as usize];
+    decode_into(comp, header, &mut out)?;
+    Ok(out)
+}
+
+/// Reverses the rice-index lookup and the left-neighbour delta, row by row, in place.
+pub fn reverse_rice_and_delta(pixels: &mut [u8], width: usize) {
+    for row in pixels.chunks_exact_mut(width) {
+        let mut prev = 0u8;
+        for ind in row.iter_mut() {
+            let delta = REV_RICE_INDEX[*ind as usize]; // rice-index -> delta
+            prev = prev.wrapping_add(delta); // delta -> pixel
+            *ind = prev;
+        }
+    }
+}
+
+/// Count's the numbers of consecutive "1" and writes them into `out`, advancing `cursor`.
+///
+/// # Data
+/// The data looks similar to "1101110" and contains numbers in unary notation which means:
+/// 0 <-> "0", 1 <-> "10", 2 <-> "110", 3 <-> "1110", etc
+pub fn decode_without_remainder_into(chunk: &[u8], out: &mut [u8], cursor: &mut usize) {
+    let mut unary = 0u8;
+    for num in chunk {
+        for bit in U8_TO_ARRAY_BOOL[*num as usize] {
+            if bit {
+                unary += 1
+            } else {
+                if let Some(slot) = out.get_mut(*cursor) {
+                    *slot = unary;
+                    *cursor += 1;
+                }
+                unary = 0
+            }
+        }
+    }
+}
+
+/// count's the numbers of consecutive "1", parses the remainder and writes them into `out`.
+///
+/// # Data
+/// The data looks similar to "110001.." and contains a number in unary notation and a remainder.
+/// The remainder contains of `rem_bits` bits. For "110001" and `rem_bits=3` we would have:
+/// "110" <-> 2 (unary notation) and "001" <-> 1 (MSB)
+/// The resulting number is (2 << 3) + 1 = 17
+///
+/// # Algorithm
+/// A number always consists of the unary part with unknown number of bits and the remainder with
+/// `rem_bits` bits. Bit-padding is only used at end of chunks and only with "1"s.
+/// This results in the "edge" case that a chunk ends with "..1111". So there can be a unary without
+/// a remainder.
+/// However there is never the case that a chunk ends with "..1101" and `rem_bits=2`. Remainders are
+/// always complete.
+pub fn decode_with_remainder_into(chunk: &[u8], out: &mut [u8], rem_bits: u8, cursor: &mut usize) {
+    // Iterating over the bits of the image
+    let mut it = chunk.iter().flat_map(|n| U8_TO_ARRAY_BOOL[*n as usize]);
+
+    loop {
+        // Determining the number of consecutive "1"
+        let mut unary = 0;
+        while let Some(true) = it.next() {
+            unary += 1;
+        }
+        // Checking if there is a remainder.
+        if let Some(bit) = it.next() {
+            let mut remainder = bit as u8;
+            for _ in 1..rem_bits {
+                // If there is a remainder, it is always complete
+                let bit = it.next().unwrap() as u8;
+                remainder = (remainder << 1) + bit;
+            }
+            if let Some(slot) = out.get_mut(*cursor) {
+                *slot = (unary << rem_bits) + remainder;
+                *cursor += 1;
+            }
+        } else {
+            break;
+        }
+    }
+}
+
+/// Same as [`decode_without_remainder_into`] but appends to a growable `Vec`, used by the
+/// parallel `std` decode path where each chunk's output length is unknown up front.
+#[cfg(feature = "alloc")]
+pub fn decode_without_remainder(chunk: &[u8], res: &mut Vec<u8>) {
+    let mut unary = 0u8;
+    for num in chunk {
+        for bit in U8_TO_ARRAY_BOOL[*num as usize] {
+            if bit {
+                unary += 1
+            } else {
+                res.push(unary);
+                unary = 0
+            }
+        }
+    }
+}
+
+/// Same as [`decode_with_remainder_into`] but appends to a growable `Vec`, used by the
+/// parallel `std` decode path where each chunk's output length is unknown up front.
+#[cfg(feature = "alloc")]
+pub fn decode_with_remainder(chunk: &[u8], res: &mut Vec<u8>, rem_bits: u8) {
+    let mut it = chunk.iter().flat_map(|n| U8_TO_ARRAY_BOOL[*n as usize]);
+
+    loop {
+        let mut unary = 0;
+        while let Some(true) = it.next() {
+            unary += 1;
+        }
+        if let Some(bit) = it.next() {
+            let mut remainder = bit as u8;
+            for _ in 1..rem_bits {
+                let bit = it.next().unwrap() as u8;
+                remainder = (remainder << 1) + bit;
+            }
+            res.push((unary << rem_bits) + remainder);
+        } else {
+            break;
+        }
+    }
+}
+
+/// Decodes a chunk encoded with the hybrid run-length/rice scheme (see `to_tgif::encode_rle`):
+/// each token starts with a type bit, followed by a varint count, followed by either one
+/// rice-coded value (a run, repeated `count` times) or `count` ordinary rice-coded values (a
+/// literal group). Falls through to the same bit-level rice decoding as
+/// [`decode_with_remainder`] for the literal-group values.
+#[cfg(feature = "alloc")]
+pub fn decode_hybrid_rle(chunk: &[u8], res: &mut Vec<u8>, rem_bits: u8) {
+    let mut it = chunk.iter().flat_map(|n| U8_TO_ARRAY_BOOL[*n as usize]);
+
+    loop {
+        let is_run = match it.next() {
+            Some(bit) => bit,
+            None => break,
+        };
+        let count = match varint_decode(&mut it) {
+            Some(count) => count as usize,
+            None => break,
+        };
+        if is_run {
+            match decode_one_rice(&mut it, rem_bits) {
+                Some(index) => res.extend(core::iter::repeat(index).take(count)),
+                None => break,
+            }
+        } else {
+            for _ in 0..count {
+                match decode_one_rice(&mut it, rem_bits) {
+                    Some(index) => res.push(index),
+                    None => return,
+                }
+            }
+        }
+    }
+}
+
+/// Decodes a single rice-coded value (unary quotient + fixed-width remainder) from a bit
+/// iterator, or `None` once the iterator runs into the chunk's trailing "1" padding.
+fn decode_one_rice<I: Iterator<Item = bool>>(it: &mut I, rem_bits: u8) -> Option<u8> {
+    let mut unary = 0u8;
+    loop {
+        if it.next()? {
+            unary += 1;
+        } else {
+            break;
+        }
+    }
+    let mut remainder = 0u8;
+    for _ in 0..rem_bits {
+        remainder = (remainder << 1) + it.next()? as u8;
+    }
+    Some((unary << rem_bits) + remainder)
+}
+
+/// Decodes a chunk produced by `to_tgif::encode_adaptive`: a leading 3-bit `k` field followed
+/// by ordinary rice-coded values using that chunk's own `k`, instead of a single `rem_bits`
+/// shared by the whole image. Selected when `Header::rem_bits == ADAPTIVE_REM_BITS`.
+#[cfg(feature = "alloc")]
+pub fn decode_adaptive(chunk: &[u8], res: &mut Vec<u8>) {
+    let mut it = chunk.iter().flat_map(|n| U8_TO_ARRAY_BOOL[*n as usize]);
+
+    let mut k = 0u8;
+    for _ in 0..3 {
+        match it.next() {
+            Some(bit) => k = (k << 1) + bit as u8,
+            None => return,
+        }
+    }
+
+    while let Some(index) = decode_one_rice(&mut it, k) {
+        res.push(index);
+    }
+}
+
+/// Decodes a LEB128-style varint (7 value bits per byte, MSB-first, leading continuation
+/// bit) from a bit iterator, mirroring `to_tgif::varint_encode`.
+fn varint_decode<I: Iterator<Item = bool>>(it: &mut I) -> Option<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let more = it.next()?;
+        let mut byte = 0u8;
+        for _ in 0..7 {
+            byte = (byte << 1) + it.next()? as u8;
+        }
+        value |= (byte as u64) << shift;
+        if !more {
+            break;
+        }
+        shift += 7;
+    }
+    Some(value)
+}
+
+/// 16-bit counterpart of [`decode_without_remainder`], for `L16` images. The unary run can
+/// exceed `u8::MAX` here, so the accumulator is widened to `u32`.
+#[cfg(feature = "alloc")]
+pub fn decode_without_remainder_16(chunk: &[u8], res: &mut Vec<u16>) {
+    let mut unary: u32 = 0;
+    for num in chunk {
+        for bit in U8_TO_ARRAY_BOOL[*num as usize] {
+            if bit {
+                unary += 1
+            } else {
+                res.push(unary as u16);
+                unary = 0
+            }
+        }
+    }
+}
+
+/// 16-bit counterpart of [`decode_with_remainder`].
+#[cfg(feature = "alloc")]
+pub fn decode_with_remainder_16(chunk: &[u8], res: &mut Vec<u16>, rem_bits: u8) {
+    let mut it = chunk.iter().flat_map(|n| U8_TO_ARRAY_BOOL[*n as usize]);
+
+    loop {
+        let mut unary: u32 = 0;
+        while let Some(true) = it.next() {
+            unary += 1;
+        }
+        if let Some(bit) = it.next() {
+            let mut remainder: u32 = bit as u32;
+            for _ in 1..rem_bits {
+                let bit = it.next().unwrap() as u32;
+                remainder = (remainder << 1) + bit;
+            }
+            res.push(((unary << rem_bits) + remainder) as u16);
+        } else {
+            break;
+        }
+    }
+}
+
+/// 16-bit counterpart of [`reverse_rice_and_delta`].
+pub fn reverse_rice_and_delta_16(pixels: &mut [u16], width: usize) {
+    for row in pixels.chunks_exact_mut(width) {
+        let mut prev = 0u16;
+        for ind in row.iter_mut() {
+            let delta = rev_rice_index_16(*ind);
+            prev = prev.wrapping_sub(delta);
+            *ind = prev;
+        }
+    }
+}
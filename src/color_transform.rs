@@ -0,0 +1,37 @@
+//! The reversible YCoCg-R color transform (Malvar & Sullivan, 2003), used by `to_tgif`/
+//! `from_tgif` to decorrelate an RGB(A) image's channels before delta+Rice coding each plane
+//! independently - the same trick PNG/FFV1 use to get grayscale-like compression on color
+//! images. Implemented entirely with wrapping `u8` arithmetic: despite `Co`/`Cg` representing
+//! signed differences, the lifting structure (each step only ever re-derives a value from
+//! bytes the inverse already has in hand) makes the transform exactly invertible modulo 256,
+//! with no extra bit of precision needed to hold the chroma planes.
+
+/// Converts one `(r, g, b)` pixel into `(y, co, cg)`. Inverted by [`from_ycocg_r`].
+pub fn to_ycocg_r(r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+    let co = r.wrapping_sub(b);
+    let t = b.wrapping_add(co >> 1);
+    let cg = g.wrapping_sub(t);
+    let y = t.wrapping_add(cg >> 1);
+    (y, co, cg)
+}
+
+/// Inverts [`to_ycocg_r`], recovering the original `(r, g, b)` pixel.
+pub fn from_ycocg_r(y: u8, co: u8, cg: u8) -> (u8, u8, u8) {
+    let t = y.wrapping_sub(cg >> 1);
+    let g = cg.wrapping_add(t);
+    let b = t.wrapping_sub(co >> 1);
+    let r = b.wrapping_add(co);
+    (r, g, b)
+}
+
+#[test]
+fn test_ycocg_r_roundtrip() {
+    for r in [0u8, 1, 17, 128, 200, 255] {
+        for g in [0u8, 1, 50, 128, 201, 255] {
+            for b in [0u8, 1, 10, 128, 199, 255] {
+                let (y, co, cg) = to_ycocg_r(r, g, b);
+                assert_eq!(from_ycocg_r(y, co, cg), (r, g, b));
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! A small table-driven CRC32 (IEEE 802.3) implementation, used to guard the
+//! header and each self-contained chunk against bit-rot or truncation - the
+//! same per-chunk validation scheme PNG uses for its own chunks.
+
+const fn build_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut a = n as u32;
+        let mut i = 0;
+        while i < 8 {
+            a = if a & 1 == 1 {
+                0xEDB8_8320 ^ (a >> 1)
+            } else {
+                a >> 1
+            };
+            i += 1;
+        }
+        table[n] = a;
+        n += 1;
+    }
+    table
+}
+
+const TABLE: [u32; 256] = build_table();
+
+/// Computes the CRC32 checksum of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc = (crc >> 8) ^ TABLE[((crc ^ byte as u32) & 0xFF) as usize];
+    }
+    !crc
+}
+
+#[test]
+fn test_crc32_known_vector() {
+    // The canonical CRC32 test vector.
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+}
+
+#[test]
+fn test_crc32_empty_input() {
+    assert_eq!(crc32(b""), 0);
+}
@@ -0,0 +1,198 @@
+//! A pull-based, incremental counterpart to [`crate::codec::decode`]. Where that function
+//! needs the whole compressed buffer up front, [`Decoder`] consumes input in whatever
+//! increments the caller has on hand (e.g. reads off a `std::io::Read`) and yields decoded
+//! scanlines as soon as they're complete, so arbitrarily large images can be decoded with a
+//! fixed-size buffer. This mirrors the state-machine design of streaming PNG decoders: bytes
+//! go in via [`Decoder::feed`], decoded rows come out via [`Decoder::next_row`].
+
+use alloc::vec::Vec;
+
+use crate::codec::{
+    check_supported, decode_hybrid_rle, decode_with_remainder, decode_without_remainder,
+    reverse_rice_and_delta,
+};
+use crate::crc32::crc32;
+use crate::error::TgifError;
+use crate::header::{Header, STARTING_INDEX};
+
+/// Where the state machine currently is in the input stream.
+#[derive(Debug)]
+enum State {
+    /// Buffering the fixed-size header, byte by byte, until it can be parsed.
+    ReadHeader,
+    /// Buffering a CRC-prefixed chunk until `remaining` more bytes have arrived.
+    ReadChunk { remaining: usize },
+    /// At least one decoded row is sitting in `ready_rows`, waiting to be pulled.
+    EmitRow,
+    /// The whole image has been emitted; further `feed` calls are no-ops.
+    Done,
+}
+
+/// Incremental decoder for the TGIF 8-bit fast path. See the module docs for the overall
+/// design.
+pub struct Decoder {
+    state: State,
+    header: Option<Header>,
+    /// Raw bytes accumulated for whichever of `ReadHeader`/`ReadChunk` is in progress.
+    buf: Vec<u8>,
+    /// Rice indices decoded from completed chunks but not yet grouped into a full row.
+    pending_indices: Vec<u8>,
+    /// Decoded pixels for complete rows not yet handed out via `next_row`.
+    ready_rows: Vec<u8>,
+    row_cursor: usize,
+    rows_emitted: u32,
+    chunk_index: usize,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Decoder {
+            state: State::ReadHeader,
+            header: None,
+            buf: Vec::new(),
+            pending_indices: Vec::new(),
+            ready_rows: Vec::new(),
+            row_cursor: 0,
+            rows_emitted: 0,
+            chunk_index: 0,
+        }
+    }
+
+    /// Feeds another slice of the `.tgif` file into the decoder and returns how many of its
+    /// bytes were consumed. Can be called repeatedly with arbitrarily small or large chunks,
+    /// e.g. straight off a fixed-size `Read` buffer. Stops consuming (without erroring) once a
+    /// row is ready and hasn't been pulled yet; the unconsumed tail of `input` is left for the
+    /// caller to feed again after draining `next_row`, which keeps memory use bounded.
+    pub fn feed(&mut self, mut input: &[u8]) -> Result<usize, TgifError> {
+        let len = input.len();
+        while !input.is_empty() {
+            match self.state {
+                State::ReadHeader => {
+                    let need = STARTING_INDEX - self.buf.len();
+                    let take = need.min(input.len());
+                    self.buf.extend_from_slice(&input[..take]);
+                    input = &input[take..];
+                    if self.buf.len() == STARTING_INDEX {
+                        let header = Header::from_u8(&self.buf)?;
+                        // This decoder hardcodes the left-neighbour delta over a single
+                        // grayscale plane with a fixed `rem_bits`; reject anything else up
+                        // front instead of mis-decoding it or panicking in `consume_chunk`.
+                        check_supported(&header, true)?;
+                        let remaining = 4 + header.chunk_size as usize / 8;
+                        self.header = Some(header);
+                        self.buf.clear();
+                        self.state = State::ReadChunk { remaining };
+                    }
+                }
+                State::ReadChunk { remaining } => {
+                    let take = remaining.min(input.len());
+                    self.buf.extend_from_slice(&input[..take]);
+                    input = &input[take..];
+                    let left = remaining - take;
+                    if left == 0 {
+                        self.consume_chunk()?;
+                    } else {
+                        self.state = State::ReadChunk { remaining: left };
+                    }
+                }
+                // The caller hasn't drained `ready_rows` yet; stop pulling in more input.
+                State::EmitRow | State::Done => break,
+            }
+        }
+        Ok(len - input.len())
+    }
+
+    /// Decodes one fully-buffered, CRC-verified chunk and folds any newly-completed rows
+    /// into `ready_rows`.
+    fn consume_chunk(&mut self) -> Result<(), TgifError> {
+        let (width, rem_bits, chunk_size, rle) = {
+            let header = self.header.as_ref().expect("chunk read before header");
+            (
+                header.width as usize,
+                header.rem_bits,
+                header.chunk_size,
+                header.rle,
+            )
+        };
+
+        if self.row_cursor > 0 {
+            self.ready_rows.drain(0..self.row_cursor);
+            self.row_cursor = 0;
+        }
+
+        let stored_crc = u32::from_be_bytes(self.buf[0..4].try_into().unwrap());
+        let payload = &self.buf[4..];
+        let computed_crc = crc32(payload);
+        if stored_crc != computed_crc {
+            return Err(TgifError::CrcMismatch {
+                chunk_index: self.chunk_index,
+                stored: stored_crc,
+                computed: computed_crc,
+            });
+        }
+        self.chunk_index += 1;
+
+        if rle {
+            decode_hybrid_rle(payload, &mut self.pending_indices, rem_bits);
+        } else if rem_bits == 0 {
+            decode_without_remainder(payload, &mut self.pending_indices);
+        } else {
+            decode_with_remainder(payload, &mut self.pending_indices, rem_bits);
+        }
+        self.buf.clear();
+
+        let complete_rows_len = (self.pending_indices.len() / width) * width;
+        let before = self.ready_rows.len();
+        self.ready_rows
+            .extend(self.pending_indices.drain(..complete_rows_len));
+        reverse_rice_and_delta(&mut self.ready_rows[before..], width);
+
+        self.state = if !self.ready_rows.is_empty() {
+            State::EmitRow
+        } else {
+            State::ReadChunk {
+                remaining: 4 + chunk_size as usize / 8,
+            }
+        };
+        Ok(())
+    }
+
+    /// Pulls the next fully-decoded row, or `None` if more input needs to be `feed`-ed first
+    /// (or the image is exhausted).
+    pub fn next_row(&mut self) -> Option<&[u8]> {
+        let (width, height, stride) = {
+            let header = self.header.as_ref()?;
+            (
+                header.width as usize,
+                header.height,
+                4 + header.chunk_size as usize / 8,
+            )
+        };
+        if self.row_cursor + width > self.ready_rows.len() {
+            return None;
+        }
+
+        let start = self.row_cursor;
+        self.row_cursor += width;
+        self.rows_emitted += 1;
+
+        if self.rows_emitted >= height {
+            self.state = State::Done;
+        } else if self.row_cursor == self.ready_rows.len() {
+            self.state = State::ReadChunk { remaining: stride };
+        }
+
+        Some(&self.ready_rows[start..self.row_cursor])
+    }
+
+    /// Whether the whole image has been emitted via `next_row`.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
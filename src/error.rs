@@ -0,0 +1,96 @@
+#[cfg(feature = "std")]
+use std::fmt;
+
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+/// Crate-wide error type returned by the fallible parts of the TGIF pipeline.
+#[derive(Debug)]
+pub enum TgifError {
+    /// The input ended before the expected amount of data could be read.
+    UnexpectedEof,
+    /// The header magic bytes or layout did not match the TGIF format.
+    BadHeader,
+    /// `src` and `dst` weren't exactly one `tgif` file and one non-`tgif` image.
+    UnsupportedConversion,
+    /// `rem_bits` was outside the valid `0..8` range.
+    InvalidRemBits(u8),
+    /// `chunk_size` was zero, which would never fit a single pixel.
+    InvalidChunkSize,
+    /// The header's format version byte doesn't match what this build understands.
+    UnsupportedVersion(u8),
+    /// `width`/`height` (or their product) exceeded the configured [`crate::limits::Limits`].
+    DimensionsTooLarge { width: u32, height: u32 },
+    /// The header uses an on-disk feature (a non-default predictor, per-chunk adaptive or
+    /// rice-partitioned Rice parameters, multi-channel planes, a bit depth other than 8, or the
+    /// hybrid RLE encoding) that this decode entry point doesn't implement. Returned instead of
+    /// silently mis-decoding or panicking; see `from_tgif::decode`/`decode_planes`/`decode_16`
+    /// for the entry points that do support these.
+    UnsupportedFeature,
+    /// A chunk's stored CRC32 didn't match the CRC32 computed over its bytes.
+    CrcMismatch {
+        chunk_index: usize,
+        stored: u32,
+        computed: u32,
+    },
+    /// Reading from or writing to disk failed.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// Encoding or decoding the surrounding image container (PNG, BMP, ...) failed.
+    #[cfg(feature = "std")]
+    Image(image::ImageError),
+}
+
+impl fmt::Display for TgifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TgifError::UnexpectedEof => write!(f, "unexpected end of file"),
+            TgifError::BadHeader => write!(f, "invalid or corrupted TGIF header"),
+            TgifError::UnsupportedConversion => {
+                write!(f, "only converting to/from TGIF is supported")
+            }
+            TgifError::InvalidRemBits(rem_bits) => {
+                write!(f, "rem_bits must be lower than 8, got {rem_bits}")
+            }
+            TgifError::InvalidChunkSize => write!(f, "chunk_size must be higher than 0"),
+            TgifError::UnsupportedVersion(version) => {
+                write!(f, "unsupported TGIF format version: {version}")
+            }
+            TgifError::DimensionsTooLarge { width, height } => {
+                write!(f, "dimensions {width}x{height} exceed the configured limits")
+            }
+            TgifError::UnsupportedFeature => {
+                write!(f, "this header uses a feature not supported by this decode entry point")
+            }
+            TgifError::CrcMismatch {
+                chunk_index,
+                stored,
+                computed,
+            } => write!(
+                f,
+                "CRC mismatch in chunk {chunk_index}: stored {stored:#010x}, computed {computed:#010x}"
+            ),
+            #[cfg(feature = "std")]
+            TgifError::Io(err) => write!(f, "I/O error: {err}"),
+            #[cfg(feature = "std")]
+            TgifError::Image(err) => write!(f, "image error: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TgifError {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for TgifError {
+    fn from(err: std::io::Error) -> Self {
+        TgifError::Io(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<image::ImageError> for TgifError {
+    fn from(err: image::ImageError) -> Self {
+        TgifError::Image(err)
+    }
+}
@@ -1,128 +1,430 @@
 use std::time::Instant;
 
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use rayon::prelude::*;
 
+use tgif::codec::{
+    decode_adaptive, decode_hybrid_rle, decode_with_remainder, decode_with_remainder_16,
+    decode_without_remainder, decode_without_remainder_16,
+};
+use tgif::color_transform::from_ycocg_r;
+use tgif::constants::{
+    rev_rice_index_16, ADAPTIVE_REM_BITS, REV_RICE_INDEX, RICE_PARTITION_REM_BITS, U8_TO_ARRAY_BOOL,
+};
+use tgif::crc32::crc32;
+use tgif::error::TgifError;
+use tgif::header::{Header, STARTING_INDEX};
+use tgif::predictor::{self, Predictor};
+use tgif::rice_partition;
+
 use crate::args::FromTGIF;
-use crate::constants::{CHUNK_SIZE, REV_RICE_INDEX, U8_TO_ARRAY_BOOL};
-use crate::header::{Header, STARTING_INDEX};
 
-pub fn run(args: &FromTGIF) {
+pub fn run(args: &FromTGIF) -> Result<(), TgifError> {
     info!("Converting {} to {}", args.src, args.dst);
 
     debug!("Reading the TGIF file from disk");
-    let tgif = std::fs::read(&args.src).unwrap_or_else(|_| panic!("Failed reading {}", &args.src));
+    let tgif = std::fs::read(&args.src)?;
 
     debug!("Parsing the header");
-    let header = Header::from_u8(&tgif);
+    let header = Header::from_u8(&tgif)?;
 
     let time = Instant::now();
     debug!("Decoding the TGIF image");
-    let img = decode(&tgif[STARTING_INDEX..], &header);
+    let (row_tags, payload) = split_row_tags(&tgif, &header)?;
 
     // Speed in Megabyte / s
-    let rate = 1.0 / time.elapsed().as_secs_f64();
-
-    debug!("Saving the original image to disk");
-    image::save_buffer(
-        &args.dst,
-        &img,
-        header.width,
-        header.height,
-        image::ColorType::L8,
-    )
-    .unwrap();
-
-    info!("Finished! Decoding speed was {rate:.3} FPS")
-}
+    match header.bit_depth {
+        16 => {
+            let img = decode_16(payload, &header, args.skip_crc_errors)?;
+            let rate = 1.0 / time.elapsed().as_secs_f64();
 
-/// Count's the numbers of consecutive "1" and adds them to a Vec<u8>
-///
-/// # Data
-/// The data looks similar to "1101110" and contains numbers in unary notation which means:
-/// 0 <-> "0", 1 <-> "10", 2 <-> "110", 3 <-> "1110", etc
-fn decode_without_remainder(chunk: &[u8], res: &mut Vec<u8>) {
-    let mut unary = 0u8;
-    for num in chunk {
-        for bit in U8_TO_ARRAY_BOOL[*num as usize] {
-            if bit {
-                unary += 1
+            debug!("Saving the original image to disk");
+            let bytes: Vec<u8> = img.into_iter().flat_map(u16::to_ne_bytes).collect();
+            image::save_buffer(
+                &args.dst,
+                &bytes,
+                header.width,
+                header.height,
+                image::ColorType::L16,
+            )?;
+            info!("Finished! Decoding speed was {rate:.3} FPS");
+        }
+        _ if header.channels > 1 => {
+            let img = decode_planes(payload, &header, row_tags, args.skip_crc_errors)?;
+            let rate = 1.0 / time.elapsed().as_secs_f64();
+
+            let color_type = if header.channels == 4 {
+                image::ColorType::Rgba8
             } else {
-                res.push(unary);
-                unary = 0
-            }
+                image::ColorType::Rgb8
+            };
+            debug!("Saving the original image to disk");
+            image::save_buffer(&args.dst, &img, header.width, header.height, color_type)?;
+            info!("Finished! Decoding speed was {rate:.3} FPS");
+        }
+        _ => {
+            let img = decode(payload, &header, row_tags, args.skip_crc_errors)?;
+            let rate = 1.0 / time.elapsed().as_secs_f64();
+
+            debug!("Saving the original image to disk");
+            image::save_buffer(
+                &args.dst,
+                &img,
+                header.width,
+                header.height,
+                image::ColorType::L8,
+            )?;
+            info!("Finished! Decoding speed was {rate:.3} FPS");
         }
     }
+
+    Ok(())
 }
 
-/// count's the numbers of consecutive "1", parses the remainder and adds them to a Vec<u8>
-///
-/// # Data
-/// The data looks similar to "110001.." and contains a number in unary notation and a remainder.
-/// The remainder contains of `rem_bits` bits. For "11001" and `rem_bits=3` we would have:
-/// "110" <-> 2 (unary notation) and "001" <-> 1 (MSB)
-/// The resulting number is (2 << 3) + 1 = 17
+/// Splits the bytes after the header into the per-scanline predictor tag section (present only
+/// when `header.predictors[0]` is [`predictor::PER_SCANLINE_TAG`] - see `to_tgif::run`) and the
+/// remaining CRC-chunked payload. Returns an empty tag slice for images that don't use
+/// per-scanline predictors. The tag section is prefixed with its own CRC32 (like every other
+/// chunk in the format), which is verified here rather than left for [`row_tags_to_predictors`]
+/// to maybe catch - a corrupted tag that still happens to land on a valid `Predictor` would
+/// otherwise silently reconstruct the wrong pixels instead of erroring out.
+pub(crate) fn split_row_tags<'a>(
+    tgif: &'a [u8],
+    header: &Header,
+) -> Result<(&'a [u8], &'a [u8]), TgifError> {
+    let comp = &tgif[STARTING_INDEX..];
+    if header.predictors[0] != predictor::PER_SCANLINE_TAG {
+        return Ok((&comp[..0], comp));
+    }
+    let tag_len = header.height as usize * header.channels.max(1) as usize;
+    if comp.len() < 4 + tag_len {
+        return Err(TgifError::UnexpectedEof);
+    }
+    let (crc_bytes, rest) = comp.split_at(4);
+    let (row_tags, payload) = rest.split_at(tag_len);
+    let stored_crc = u32::from_be_bytes(
+        crc_bytes
+            .try_into()
+            .expect("split_at(4) guarantees 4 bytes"),
+    );
+    let computed = crc32(row_tags);
+    if computed != stored_crc {
+        // The row-tags section isn't one of the numbered payload chunks; it's reported as
+        // `chunk_index: 0`, the same index the first payload chunk gets in
+        // `chunks`/`verified_chunks`.
+        return Err(TgifError::CrcMismatch {
+            chunk_index: 0,
+            stored: stored_crc,
+            computed,
+        });
+    }
+    Ok((row_tags, payload))
+}
+
+/// A chunk as laid out on disk: a 4-byte CRC32 followed by the CRC-covered payload bytes.
+struct Chunk<'a> {
+    index: usize,
+    stored_crc: u32,
+    payload: &'a [u8],
+}
+
+/// Splits the per-chunk-CRC payload (everything after the header) into its chunks. Errors if
+/// a trailing group is too short to even hold the 4-byte CRC prefix - a truncated or corrupted
+/// file, rather than a short final chunk (which `comp.chunks(stride)` always yields, since
+/// `stride` rarely evenly divides `comp.len()`). Every byte access below this point, here and
+/// in [`Header::from_u8`], is reachable only after a length check, so a truncated or crafted
+/// file is rejected with a [`TgifError`] instead of panicking on an out-of-bounds index.
+fn chunks(comp: &[u8], chunk_size: u32) -> Result<impl Iterator<Item = Chunk<'_>>, TgifError> {
+    let stride = 4 + chunk_size as usize / 8;
+    comp.chunks(stride)
+        .enumerate()
+        .map(|(index, raw)| {
+            if raw.len() < 4 {
+                return Err(TgifError::UnexpectedEof);
+            }
+            Ok(Chunk {
+                index,
+                stored_crc: u32::from_be_bytes(
+                    raw[0..4].try_into().expect("checked raw.len() >= 4 above"),
+                ),
+                payload: &raw[4..],
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Vec::into_iter)
+}
+
+/// Verifies (and optionally tolerates) the CRC32 of every chunk, running the verification
+/// itself in parallel since chunks are independent.
+fn verified_chunks<'a>(
+    comp: &'a [u8],
+    header: &Header,
+    skip_crc_errors: bool,
+) -> Result<Vec<Option<&'a [u8]>>, TgifError> {
+    chunks(comp, header.chunk_size)?
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|chunk| {
+            let computed = crc32(chunk.payload);
+            if computed == chunk.stored_crc {
+                return Ok(Some(chunk.payload));
+            }
+            if skip_crc_errors {
+                warn!(
+                    "CRC mismatch in chunk {}: stored {:#010x}, computed {:#010x} - skipping",
+                    chunk.index, chunk.stored_crc, computed
+                );
+                return Ok(None);
+            }
+            Err(TgifError::CrcMismatch {
+                chunk_index: chunk.index,
+                stored: chunk.stored_crc,
+                computed,
+            })
+        })
+        .collect()
+}
+
+/// Parallel decode over independent `CHUNK_SIZE` blocks, kept in the `std` binary since it
+/// relies on rayon; the actual bit-unpacking lives in `tgif::codec` and is `no_std`-safe.
 ///
-/// # Algorithm
-/// A number always consists of the unary part with unknown number of bits and the remainder with
-/// `rem_bits` bits. Bit-padding is only used at end of chunks and only with "1"s.
-/// This results in the "edge" case that a chunk ends with "..1111". So there can be a unary without
-/// a remainder.
-/// However there is never the case that a chunk ends with "..1101" and `rem_bits=2`. Remainders are
-/// always complete.
-fn decode_with_remainder(chunk: &[u8], res: &mut Vec<u8>, rem_bits: u8) {
-    // Iterating over the bits of the image
-    let mut it = chunk.iter().flat_map(|n| U8_TO_ARRAY_BOOL[*n as usize]);
-
-    loop {
-        // Determining the number of consecutive "1"
-        let mut unary = 0;
-        while let Some(true) = it.next() {
-            unary += 1;
+/// `pub(crate)` so `view::run` can reuse it for terminal previews instead of duplicating it.
+pub(crate) fn decode(
+    comp: &[u8],
+    header: &Header,
+    row_tags: &[u8],
+    skip_crc_errors: bool,
+) -> Result<Vec<u8>, TgifError> {
+    if header.width == 0 {
+        return Err(TgifError::BadHeader);
+    }
+
+    // Validated up front, before the expensive parallel residual decode below, so a corrupt or
+    // unrecognized predictor tag fails fast instead of paying for a decode whose result gets
+    // thrown away.
+    let tags = if header.predictors[0] == predictor::PER_SCANLINE_TAG {
+        Some(row_tags_to_predictors(row_tags)?)
+    } else {
+        Predictor::from_tag(header.predictors[0]).ok_or(TgifError::BadHeader)?;
+        None
+    };
+
+    let residuals = decode_residuals(comp, header, skip_crc_errors)?;
+
+    // Reversing the predictor isn't embarrassingly parallel like the rice index: `Up`/`Med`
+    // need the row above's already-reconstructed pixels, so this runs as one sequential pass
+    // over the image.
+    let time = Instant::now();
+    let img = if let Some(tags) = tags {
+        predictor::invert_rows(&tags, &residuals, header.width as usize)
+    } else {
+        let predictor = Predictor::from_tag(header.predictors[0]).expect("validated above");
+        predictor::invert(predictor, &residuals, header.width as usize)
+    };
+    trace!("Time for reversing the predictor: {:?}", time.elapsed());
+
+    Ok(img)
+}
+
+/// Converts a raw per-row predictor-tag section (see [`split_row_tags`]) into `Predictor`s,
+/// rejecting any byte that isn't a valid tag instead of letting [`predictor::invert_rows`]
+/// panic on an unrecognized one.
+fn row_tags_to_predictors(row_tags: &[u8]) -> Result<Vec<Predictor>, TgifError> {
+    row_tags
+        .iter()
+        .map(|&tag| Predictor::from_tag(tag).ok_or(TgifError::BadHeader))
+        .collect()
+}
+
+/// Decodes `comp` into one flat residual stream (post rice-index-reversal, pre-predictor-
+/// inversion), shared by [`decode`] and [`decode_planes`] - the only difference between a
+/// grayscale and an RGB(A) image at this stage is how many causally-independent planes get
+/// concatenated into that stream.
+fn decode_residuals(
+    comp: &[u8],
+    header: &Header,
+    skip_crc_errors: bool,
+) -> Result<Vec<u8>, TgifError> {
+    if header.rem_bits == RICE_PARTITION_REM_BITS {
+        return decode_residuals_partitioned(comp, header, skip_crc_errors);
+    }
+
+    let time = Instant::now();
+    let rice_ind: Vec<u8> = verified_chunks(comp, header, skip_crc_errors)?
+        .into_par_iter()
+        .flat_map(|payload| {
+            // Doesn't reallocate in the case of 50 % compression rate
+            let mut res: Vec<u8> = Vec::new();
+            if let Some(payload) = payload {
+                res.reserve(payload.len() * 4);
+                if header.rle {
+                    decode_hybrid_rle(payload, &mut res, header.rem_bits);
+                } else if header.rem_bits == ADAPTIVE_REM_BITS {
+                    decode_adaptive(payload, &mut res);
+                } else if header.rem_bits == 0 {
+                    decode_without_remainder(payload, &mut res);
+                } else {
+                    decode_with_remainder(payload, &mut res, header.rem_bits);
+                }
+            }
+            res
+        })
+        .collect();
+    trace!("Time for decompression: {:?}", time.elapsed());
+
+    let time = Instant::now();
+    let residuals: Vec<u8> = rice_ind
+        .into_par_iter()
+        .map(|ind| REV_RICE_INDEX[ind as usize])
+        .collect();
+    trace!("Time for reversing the rice index: {:?}", time.elapsed());
+
+    Ok(residuals)
+}
+
+/// Counterpart of [`to_tgif::encode_rice_partitioned`]: undoes a [`RICE_PARTITION_REM_BITS`]
+/// plane. Unlike the other modes, a rice-partitioned plane isn't independently decodable chunk
+/// by chunk - `rice_partition::decode_partitioned` needs the whole bitstream at once - so this
+/// concatenates every CRC-verified chunk payload back into one buffer first (a dropped chunk
+/// would desync every partition boundary after it, so `--skip-crc-errors` can't be honored here)
+/// before unpacking it into bits and handing it to [`rice_partition::decode_partitioned`].
+fn decode_residuals_partitioned(
+    comp: &[u8],
+    header: &Header,
+    skip_crc_errors: bool,
+) -> Result<Vec<u8>, TgifError> {
+    let payload: Vec<u8> = verified_chunks(comp, header, skip_crc_errors)?
+        .into_iter()
+        .map(|chunk| chunk.ok_or(TgifError::UnexpectedEof))
+        .collect::<Result<Vec<&[u8]>, _>>()?
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect();
+
+    let bits: Vec<bool> = payload
+        .iter()
+        .flat_map(|&b| U8_TO_ARRAY_BOOL[b as usize])
+        .collect();
+
+    let count = header.width as usize * header.height as usize * header.channels.max(1) as usize;
+    let rice_ind = rice_partition::decode_partitioned(&bits, count);
+
+    let residuals: Vec<u8> = rice_ind
+        .into_par_iter()
+        .map(|ind| REV_RICE_INDEX[ind as usize])
+        .collect();
+
+    Ok(residuals)
+}
+
+/// RGB(A) counterpart of [`decode`]: splits the flat residual stream into `header.channels`
+/// equal-length planes (mirroring [`crate::to_tgif::planarize`]'s concatenation), inverts each
+/// plane's own predictor, then reinterleaves the planes back into pixels - undoing the YCoCg-R
+/// transform on the first three channels first if `header.color_transform` is set.
+pub(crate) fn decode_planes(
+    comp: &[u8],
+    header: &Header,
+    row_tags: &[u8],
+    skip_crc_errors: bool,
+) -> Result<Vec<u8>, TgifError> {
+    if header.width == 0 {
+        return Err(TgifError::BadHeader);
+    }
+
+    let width = header.width as usize;
+    let height = header.height as usize;
+    let channels = header.channels as usize;
+
+    // Validated up front, before the expensive parallel residual decode below, so a corrupt or
+    // unrecognized predictor tag fails fast instead of paying for a decode whose result gets
+    // thrown away.
+    if header.predictors[0] == predictor::PER_SCANLINE_TAG {
+        if row_tags.len() != height * channels {
+            return Err(TgifError::UnexpectedEof);
+        }
+        row_tags_to_predictors(row_tags)?;
+    } else {
+        for &tag in &header.predictors[..channels] {
+            Predictor::from_tag(tag).ok_or(TgifError::BadHeader)?;
         }
-        // Checking if there is a remainder.
-        if let Some(bit) = it.next() {
-            let mut remainder = bit as u8;
-            for _ in 1..rem_bits {
-                // If there is a remainder, it is always complete
-                let bit = it.next().unwrap() as u8;
-                remainder = (remainder << 1) + bit;
+    }
+
+    let residuals = decode_residuals(comp, header, skip_crc_errors)?;
+
+    let plane_len = residuals.len() / channels;
+
+    let time = Instant::now();
+    let planes: Vec<Vec<u8>> = residuals
+        .chunks_exact(plane_len)
+        .enumerate()
+        .map(|(channel, plane_residuals)| {
+            if header.predictors[0] == predictor::PER_SCANLINE_TAG {
+                let channel_tags = &row_tags[channel * height..(channel + 1) * height];
+                let tags = row_tags_to_predictors(channel_tags).expect("validated above");
+                predictor::invert_rows(&tags, plane_residuals, width)
+            } else {
+                let predictor =
+                    Predictor::from_tag(header.predictors[channel]).expect("validated above");
+                predictor::invert(predictor, plane_residuals, width)
             }
-            res.push((unary << rem_bits) + remainder);
+        })
+        .collect();
+    trace!("Time for reversing the predictors: {:?}", time.elapsed());
+
+    let mut img = Vec::with_capacity(residuals.len());
+    for pixel in 0..plane_len {
+        let (r, g, b) = if header.color_transform {
+            from_ycocg_r(planes[0][pixel], planes[1][pixel], planes[2][pixel])
         } else {
-            break;
+            (planes[0][pixel], planes[1][pixel], planes[2][pixel])
+        };
+        img.push(r);
+        img.push(g);
+        img.push(b);
+        if channels == 4 {
+            img.push(planes[3][pixel]);
         }
     }
+
+    Ok(img)
 }
 
-fn decode(comp: &[u8], header: &Header) -> Vec<u8> {
-    let time = Instant::now();
-    // Chunks must be dividable into bytes
-    assert_eq!(CHUNK_SIZE % 8, 0);
-    let mut rice_ind = comp
-        .par_chunks(CHUNK_SIZE / 8)
-        .flat_map(|chunk| {
-            // Doesn't reallocate in the case of 50 % compression rate
-            let mut res: Vec<u8> = Vec::with_capacity(CHUNK_SIZE / 2);
+/// 16-bit counterpart of [`decode`], used for `L16` images.
+pub(crate) fn decode_16(
+    comp: &[u8],
+    header: &Header,
+    skip_crc_errors: bool,
+) -> Result<Vec<u16>, TgifError> {
+    if header.width == 0 {
+        return Err(TgifError::BadHeader);
+    }
 
-            if header.rem_bits == 0 {
-                decode_without_remainder(chunk, &mut res);
-            } else {
-                decode_with_remainder(chunk, &mut res, header.rem_bits);
+    let time = Instant::now();
+    let mut rice_ind: Vec<u16> = verified_chunks(comp, header, skip_crc_errors)?
+        .into_par_iter()
+        .flat_map(|payload| {
+            let mut res: Vec<u16> = Vec::new();
+            if let Some(payload) = payload {
+                res.reserve(payload.len() * 4);
+                if header.rem_bits == 0 {
+                    decode_without_remainder_16(payload, &mut res);
+                } else {
+                    decode_with_remainder_16(payload, &mut res, header.rem_bits);
+                }
             }
             res
         })
-        .collect::<Vec<u8>>();
+        .collect();
     trace!("Time for decompression: {:?}", time.elapsed());
 
     let time = Instant::now();
-
     rice_ind
         .par_chunks_exact_mut(header.width as usize)
         .for_each(|chunk| {
-            let mut prev = 0u8;
+            let mut prev = 0u16;
             for ind in chunk {
-                let delta = REV_RICE_INDEX[*ind as usize]; // rice-index -> delta
+                let delta = rev_rice_index_16(*ind); // rice-index -> delta
                 prev = prev.wrapping_sub(delta); // delta -> pixel
                 *ind = prev
             }
@@ -132,5 +434,5 @@ fn decode(comp: &[u8], header: &Header) -> Vec<u8> {
         time.elapsed()
     );
 
-    rice_ind
+    Ok(rice_ind)
 }
@@ -1,29 +1,90 @@
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 use log::trace;
 
-pub const STARTING_INDEX: usize = 17;
+use crate::crc32::crc32;
+use crate::error::TgifError;
+use crate::limits::Limits;
+
+/// Format version written into every header. Bumped whenever the on-disk layout changes
+/// so older files are rejected with a clear error instead of being silently misread.
+pub const FORMAT_VERSION: u8 = 6;
+
+/// Byte length of the header, including its own trailing CRC32.
+pub const STARTING_INDEX: usize = 30;
 
 #[derive(Debug, Clone)]
 pub struct Header {
+    #[cfg(feature = "alloc")]
     pub name: String,
     pub height: u32,
     pub width: u32,
     pub chunk_size: u32,
     pub rem_bits: u8,
+    /// Pixel bit depth: `8` for the `L8` fast path, `16` for `L16`. Limited to these two
+    /// values because the `image` crate itself only exposes 8-bit and 16-bit grayscale
+    /// decoding (`to_luma8`/`to_luma16`) - there's no source format this CLI can read that
+    /// would supply, say, a 10-bit or 12-bit sample to widen the Rice/delta math for.
+    pub bit_depth: u8,
+    /// Whether chunks use the hybrid run-length/rice encoding instead of the plain rice
+    /// stream.
+    pub rle: bool,
+    /// Number of channels making up each pixel: `1` for plain grayscale, `3` for RGB, `4` for
+    /// RGBA. Every channel is its own independently predicted plane; the planes' residuals are
+    /// concatenated in channel order and rice-coded as one stream, so a chunk can span two
+    /// planes (see `to_tgif::run`).
+    pub channels: u8,
+    /// Whether the RGB channels were run through [`crate::color_transform::to_ycocg_r`]
+    /// before prediction. Only meaningful when `channels >= 3`; an RGBA image's 4th (alpha)
+    /// plane is never transformed.
+    pub color_transform: bool,
+    /// `crate::predictor::Predictor::tag()` of the spatial predictor used for each plane, one
+    /// slot per channel (unused trailing slots for `channels < 4` are left as `0`). Stored as
+    /// raw bytes (rather than the `Predictor` type itself) so this `no_std` module doesn't have
+    /// to depend on the `alloc`-gated `predictor` module.
+    pub predictors: [u8; 4],
+    pub version: u8,
 }
 
 impl Header {
-    pub fn new(width: u32, height: u32, chunk_size: u32, rem_bits: u8) -> Self {
+    #[cfg(feature = "alloc")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        width: u32,
+        height: u32,
+        chunk_size: u32,
+        rem_bits: u8,
+        bit_depth: u8,
+        rle: bool,
+        channels: u8,
+        color_transform: bool,
+        predictors: [u8; 4],
+    ) -> Self {
         Header {
             name: "TGIF".to_string(),
             height,
             width,
             chunk_size,
             rem_bits,
+            bit_depth,
+            rle,
+            channels,
+            color_transform,
+            predictors,
+            version: FORMAT_VERSION,
         }
     }
 
+    /// Serializes the header, appending a trailing CRC32 over every preceding byte (verified by
+    /// [`Header::from_u8`]) so a corrupted or truncated header is rejected before it's ever used
+    /// to interpret the payload, rather than letting a garbled `width`/`height`/`predictors`
+    /// silently mis-decode the rest of the file.
+    #[cfg(feature = "alloc")]
     pub fn to_u8(&self) -> Vec<u8> {
-        [
+        let mut buf: Vec<u8> = [
             u32::from_be_bytes(*b"TGIF"),
             self.height,
             self.width,
@@ -31,19 +92,74 @@ impl Header {
         ]
         .into_iter()
         .flat_map(|v| v.to_be_bytes())
-        .chain(std::iter::once(self.rem_bits))
-        .collect()
+        .chain(core::iter::once(self.rem_bits))
+        .chain(core::iter::once(self.bit_depth))
+        .chain(core::iter::once(self.rle as u8))
+        .chain(core::iter::once(self.predictors[0]))
+        .chain(core::iter::once(self.channels))
+        .chain(core::iter::once(self.color_transform as u8))
+        .chain(core::iter::once(self.predictors[1]))
+        .chain(core::iter::once(self.predictors[2]))
+        .chain(core::iter::once(self.predictors[3]))
+        .chain(core::iter::once(self.version))
+        .collect();
+
+        buf.extend(crc32(&buf).to_be_bytes());
+        buf
     }
 
-    pub fn from_u8(img: &[u8]) -> Self {
+    pub fn from_u8(img: &[u8]) -> Result<Self, TgifError> {
+        if img.len() < STARTING_INDEX {
+            return Err(TgifError::UnexpectedEof);
+        }
+        if &img[0..4] != b"TGIF" {
+            return Err(TgifError::BadHeader);
+        }
+
+        let version = img[25];
+        if version != FORMAT_VERSION {
+            return Err(TgifError::UnsupportedVersion(version));
+        }
+
+        let stored_crc = Self::slice_u8_as_u32_be(&img[26..30]);
+        let computed_crc = crc32(&img[0..26]);
+        if stored_crc != computed_crc {
+            return Err(TgifError::BadHeader);
+        }
+
+        // Only 8 and 16 are ever written by `to_tgif` (see `Header::bit_depth`'s doc comment
+        // for why); anything else means a corrupt or foreign-writer header.
+        let bit_depth = img[17];
+        if bit_depth != 8 && bit_depth != 16 {
+            return Err(TgifError::BadHeader);
+        }
+
+        let channels = img[20];
+        if channels != 1 && channels != 3 && channels != 4 {
+            return Err(TgifError::BadHeader);
+        }
+
+        let height = Self::slice_u8_as_u32_be(&img[4..8]);
+        let width = Self::slice_u8_as_u32_be(&img[8..12]);
+        // Reject absurd dimensions up front, before any caller sizes an allocation off of
+        // them - a crafted header is otherwise free to claim e.g. `width = height = u32::MAX`.
+        Limits::default().check_dimensions(width, height)?;
+
         trace!("Reading header from image");
-        Header {
+        Ok(Header {
+            #[cfg(feature = "alloc")]
             name: "TGIF".to_string(),
-            height: Self::slice_u8_as_u32_be(&img[4..8]),
-            width: Self::slice_u8_as_u32_be(&img[8..12]),
+            height,
+            width,
             chunk_size: Self::slice_u8_as_u32_be(&img[12..16]),
             rem_bits: img[16],
-        }
+            bit_depth,
+            rle: img[18] != 0,
+            channels,
+            color_transform: img[21] != 0,
+            predictors: [img[19], img[22], img[23], img[24]],
+            version,
+        })
     }
 
     fn slice_u8_as_u32_be(array: &[u8]) -> u32 {
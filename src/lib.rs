@@ -0,0 +1,31 @@
+//! Core TGIF codec: header parsing and the Rice/delta decode pipeline.
+//!
+//! This crate is `no_std` by default so the codec can run in embedded or WASM
+//! contexts where the input is already sitting in memory. Enable the `std`
+//! feature (the default for the `tgif` binary) to pull in `std::error::Error`
+//! impls and conversions from `std::io::Error`/`image::ImageError`. Enable
+//! `alloc` (implied by `std`) for the `Vec`-returning convenience APIs.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod codec;
+pub mod color_transform;
+pub mod constants;
+pub mod crc32;
+#[cfg(feature = "alloc")]
+pub mod decoder;
+pub mod error;
+pub mod header;
+pub mod limits;
+#[cfg(feature = "alloc")]
+pub mod predictor;
+#[cfg(feature = "alloc")]
+pub mod render;
+#[cfg(feature = "alloc")]
+pub mod rice_partition;
+#[cfg(feature = "alloc")]
+pub mod roi;
+#[cfg(feature = "alloc")]
+pub mod tensor;
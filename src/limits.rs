@@ -0,0 +1,65 @@
+//! Guards against pathological `width`/`height` values so a crafted TGIF header can't make the
+//! codec commit to an absurd allocation before any real decoding work has happened.
+
+use crate::error::TgifError;
+
+/// Upper bounds checked before `width`/`height` are used to size an allocation. Tune these
+/// down for a stricter embedded or server deployment; the [`Default`] impl is generous enough
+/// for any real image while still rejecting the kind of `width = height = u32::MAX` header a
+/// fuzzer (or attacker) would hand the decoder.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_width: u32,
+    pub max_height: u32,
+    pub max_alloc_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_width: 1 << 16,
+            max_height: 1 << 16,
+            max_alloc_bytes: 1 << 30, // 1 GiB
+        }
+    }
+}
+
+impl Limits {
+    /// Validates `width`/`height` against `max_width`/`max_height`, then validates their
+    /// product - computed with checked multiplication, since a naive `width * height` can
+    /// silently overflow `usize` on 32-bit targets - against `max_alloc_bytes`. Returns the
+    /// validated pixel count.
+    pub fn check_dimensions(&self, width: u32, height: u32) -> Result<usize, TgifError> {
+        if width > self.max_width || height > self.max_height {
+            return Err(TgifError::DimensionsTooLarge { width, height });
+        }
+        (width as usize)
+            .checked_mul(height as usize)
+            .filter(|&pixels| pixels <= self.max_alloc_bytes)
+            .ok_or(TgifError::DimensionsTooLarge { width, height })
+    }
+}
+
+#[test]
+fn test_check_dimensions_rejects_overflow() {
+    let limits = Limits { max_width: u32::MAX, max_height: u32::MAX, max_alloc_bytes: usize::MAX };
+    assert!(matches!(
+        limits.check_dimensions(u32::MAX, u32::MAX),
+        Err(TgifError::DimensionsTooLarge { .. })
+    ));
+}
+
+#[test]
+fn test_check_dimensions_rejects_oversized_dimensions() {
+    let limits = Limits::default();
+    assert!(matches!(
+        limits.check_dimensions(limits.max_width + 1, 1),
+        Err(TgifError::DimensionsTooLarge { .. })
+    ));
+}
+
+#[test]
+fn test_check_dimensions_accepts_reasonable_image() {
+    let limits = Limits::default();
+    assert_eq!(limits.check_dimensions(1920, 1080).unwrap(), 1920 * 1080);
+}
@@ -5,10 +5,11 @@ use crate::args::Operation;
 use log::{debug, LevelFilter};
 
 mod args;
-mod constants;
+mod bit_writer;
 mod from_tgif;
-mod header;
+mod parallel;
 mod to_tgif;
+mod view;
 
 fn main() {
     // Setting up the logging environment
@@ -28,11 +29,17 @@ fn main() {
         .init();
 
     debug!("Parsing arguments from CLI");
-    let args: Operation = args::Cli::parse().verify_arguments();
-    debug!("Arguments: {:?}", &args);
+    let result = args::Cli::parse().verify_arguments().and_then(|args| {
+        debug!("Arguments: {:?}", &args);
+        match &args {
+            Operation::ToTGIF(args) => to_tgif::run(args),
+            Operation::FromTGIF(args) => from_tgif::run(args),
+            Operation::View(args) => view::run(args),
+        }
+    });
 
-    match &args {
-        Operation::ToTGIF(args) => to_tgif::run(args),
-        Operation::FromTGIF(args) => from_tgif::run(args),
+    if let Err(err) = result {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
     }
 }
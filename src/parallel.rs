@@ -0,0 +1,99 @@
+//! Multi-threaded counterparts of [`tgif::roi::encode_indexed`]/[`tgif::roi::decode_roi`].
+//!
+//! Row bands produced by the `roi` module are already independently encodable/decodable, so
+//! the only new ingredient here is farming them out to rayon instead of looping serially;
+//! `num_threads` is the granularity knob for how many worker threads to use (`None` defers to
+//! rayon's own default, based on available parallelism).
+//!
+//! Like [`tgif::roi`] itself, nothing in the CLI calls this yet: it operates on the
+//! indexed-band layout, which the `tgif` binary's `--src`/`--dst` conversion never writes or
+//! reads (that pipeline is already parallel across its own `CHUNK_SIZE` blocks via rayon - see
+//! `to_tgif::run`/`from_tgif::decode` - just not via this module's band-level granularity).
+//! A CLI `--threads` knob for this would only make sense once the indexed-band format itself
+//! has a CLI entry point (see `roi`'s module doc).
+
+use rayon::prelude::*;
+
+use tgif::error::TgifError;
+use tgif::roi::{band_bytes, decode_band, encode_band, ChunkEntry};
+
+/// Rice-codes `pixels` in independent bands of up to `rows_per_band` rows each, encoding the
+/// bands concurrently and concatenating them back together in order.
+pub fn encode_parallel(
+    pixels: &[u8],
+    width: u32,
+    rem_bits: u8,
+    rows_per_band: u32,
+    num_threads: Option<usize>,
+) -> (Vec<u8>, Vec<ChunkEntry>) {
+    run_with_pool(num_threads, || {
+        let width_usize = width as usize;
+        let height = (pixels.len() / width_usize) as u32;
+
+        let mut bands = Vec::new();
+        let mut row_start = 0u32;
+        while row_start < height {
+            let row_count = rows_per_band.min(height - row_start);
+            bands.push((row_start, row_count));
+            row_start += row_count;
+        }
+
+        let encoded: Vec<Vec<u8>> = bands
+            .par_iter()
+            .map(|&(row_start, row_count)| {
+                let band = &pixels[row_start as usize * width_usize
+                    ..(row_start + row_count) as usize * width_usize];
+                encode_band(band, width_usize, rem_bits)
+            })
+            .collect();
+
+        let mut bytes = Vec::new();
+        let mut index = Vec::with_capacity(bands.len());
+        for ((row_start, row_count), chunk) in bands.into_iter().zip(encoded) {
+            index.push(ChunkEntry {
+                byte_offset: bytes.len() as u32,
+                row_start,
+                row_count,
+            });
+            bytes.extend(chunk);
+        }
+        (bytes, index)
+    })
+}
+
+/// Decodes every band in `index` concurrently and concatenates the results in order,
+/// reconstructing the whole image.
+pub fn decode_parallel(
+    bytes: &[u8],
+    index: &[ChunkEntry],
+    width: u32,
+    rem_bits: u8,
+    num_threads: Option<usize>,
+) -> Result<Vec<u8>, TgifError> {
+    run_with_pool(num_threads, || {
+        let width_usize = width as usize;
+        let decoded: Result<Vec<Vec<u8>>, TgifError> = (0..index.len())
+            .into_par_iter()
+            .map(|chunk_index| {
+                let chunk = band_bytes(bytes, index, chunk_index)?;
+                decode_band(chunk, width_usize, rem_bits, chunk_index)
+            })
+            .collect();
+        Ok(decoded?.into_iter().flatten().collect())
+    })
+}
+
+/// Runs `f` on rayon's global pool, or on a scoped pool of `num_threads` workers if given.
+fn run_with_pool<T>(num_threads: Option<usize>, f: impl FnOnce() -> T + Send) -> T
+where
+    T: Send,
+{
+    match num_threads {
+        Some(num_threads) => rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .expect("failed to build a rayon thread pool")
+            .install(f),
+        None => f(),
+    }
+}
@@ -0,0 +1,287 @@
+//! Pluggable spatial predictors, selected per chunk - the same idea as PNG's per-scanline
+//! filter byte and HDF5's per-dataset filter chains. A predictor turns each pixel into a
+//! residual relative to its causal neighbours (no prediction / left / up / average / Paeth /
+//! MED); [`best_predictor`] trial-applies all of them and keeps whichever minimizes the total
+//! zigzag-mapped residual magnitude, which is a good proxy for the Rice-coded size. Composes
+//! with [`crate::roi`], whose bands record the winning predictor as a one-byte tag.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::constants::RICE_INDEX;
+
+/// A spatial predictor choosable per chunk, recorded on disk as its `tag()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Predictor {
+    /// No prediction: the residual is the raw pixel value.
+    None,
+    /// Predicts the left neighbour (the delta scheme used elsewhere in the codec).
+    Left,
+    /// Predicts the pixel directly above.
+    Up,
+    /// Predicts the (floor) average of the left and above neighbours.
+    Average,
+    /// PNG's Paeth predictor: picks whichever of left/up/upper-left is closest to
+    /// `left + up - upper_left`.
+    Paeth,
+    /// The MED/LOCO-I predictor from JPEG-LS: `min(a, b)` if the upper-left neighbour is
+    /// the largest of the three, `max(a, b)` if it's the smallest, otherwise `a + b - c`.
+    /// Tracks local edges better than a fixed linear combination of neighbours.
+    Med,
+}
+
+const ALL: [Predictor; 6] = [
+    Predictor::None,
+    Predictor::Left,
+    Predictor::Up,
+    Predictor::Average,
+    Predictor::Paeth,
+    Predictor::Med,
+];
+
+/// Sentinel `Header::predictors[0]` value meaning "chosen independently per scanline", PNG-style,
+/// rather than one predictor for the whole plane. Outside the `0..=5` range every real
+/// [`Predictor::tag`] lives in, so it can't collide with one. The per-row tags themselves don't
+/// fit in `Header::predictors` (only one slot per channel), so they're instead written as a small
+/// uncompressed section right after the header - see `to_tgif::run`/`from_tgif::split_row_tags`.
+pub const PER_SCANLINE_TAG: u8 = 255;
+
+impl Predictor {
+    /// Predicts a pixel from its left (`a`), up (`b`) and upper-left (`c`) neighbours, all
+    /// `0` past the edge of the image.
+    fn predict(self, a: u8, b: u8, c: u8) -> u8 {
+        match self {
+            Predictor::None => 0,
+            Predictor::Left => a,
+            Predictor::Up => b,
+            Predictor::Average => ((a as u16 + b as u16) / 2) as u8,
+            Predictor::Paeth => paeth(a, b, c),
+            Predictor::Med => med(a, b, c),
+        }
+    }
+
+    /// One-byte on-disk tag for this predictor.
+    pub fn tag(self) -> u8 {
+        match self {
+            Predictor::None => 0,
+            Predictor::Left => 1,
+            Predictor::Up => 2,
+            Predictor::Average => 3,
+            Predictor::Paeth => 4,
+            Predictor::Med => 5,
+        }
+    }
+
+    /// Inverts [`Predictor::tag`].
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        ALL.into_iter().find(|p| p.tag() == tag)
+    }
+}
+
+/// PNG's Paeth predictor.
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i32 + b as i32 - c as i32;
+    let pa = (p - a as i32).abs();
+    let pb = (p - b as i32).abs();
+    let pc = (p - c as i32).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// The MED/LOCO-I predictor from JPEG-LS.
+fn med(a: u8, b: u8, c: u8) -> u8 {
+    let (lo, hi) = (a.min(b), a.max(b));
+    if c >= hi {
+        lo
+    } else if c <= lo {
+        hi
+    } else {
+        a.wrapping_add(b).wrapping_sub(c)
+    }
+}
+
+/// Applies `predictor` to `pixels` (row-major, `width` wide), returning one residual per
+/// pixel.
+#[cfg(feature = "alloc")]
+pub fn apply(predictor: Predictor, pixels: &[u8], width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(pixels.len());
+    for (row, chunk) in pixels.chunks_exact(width).enumerate() {
+        for (col, &pixel) in chunk.iter().enumerate() {
+            let a = if col > 0 { chunk[col - 1] } else { 0 };
+            let b = if row > 0 { pixels[(row - 1) * width + col] } else { 0 };
+            let c = if row > 0 && col > 0 {
+                pixels[(row - 1) * width + col - 1]
+            } else {
+                0
+            };
+            out.push(pixel.wrapping_sub(predictor.predict(a, b, c)));
+        }
+    }
+    out
+}
+
+/// Inverts [`apply`], reconstructing the original pixels from `residuals`.
+#[cfg(feature = "alloc")]
+pub fn invert(predictor: Predictor, residuals: &[u8], width: usize) -> Vec<u8> {
+    let mut out = alloc::vec![0u8; residuals.len()];
+    let height = residuals.len() / width;
+    for row in 0..height {
+        for col in 0..width {
+            let index = row * width + col;
+            let a = if col > 0 { out[index - 1] } else { 0 };
+            let b = if row > 0 { out[index - width] } else { 0 };
+            let c = if row > 0 && col > 0 { out[index - width - 1] } else { 0 };
+            out[index] = residuals[index].wrapping_add(predictor.predict(a, b, c));
+        }
+    }
+    out
+}
+
+/// Trial-applies every predictor to `pixels` and returns whichever minimizes the total
+/// zigzag-mapped residual magnitude (a cheap proxy for the eventual Rice-coded size), along
+/// with the residuals it produced.
+#[cfg(feature = "alloc")]
+pub fn best_predictor(pixels: &[u8], width: usize) -> (Predictor, Vec<u8>) {
+    ALL.into_iter()
+        .map(|predictor| {
+            let residuals = apply(predictor, pixels, width);
+            let cost: usize = residuals
+                .iter()
+                .map(|&r| RICE_INDEX[r as usize] as usize)
+                .sum();
+            (predictor, residuals, cost)
+        })
+        .min_by_key(|(_, _, cost)| *cost)
+        .map(|(predictor, residuals, _)| (predictor, residuals))
+        .expect("ALL is non-empty")
+}
+
+/// Applies `predictor` to a single row (PNG's "Sub"/"Up"/"Average"/"Paeth" predict from the
+/// causal neighbours the same way regardless of row, only the choice of predictor varies), using
+/// the original, unpredicted `pixels` for the row above - the same convention [`apply`] uses.
+#[cfg(feature = "alloc")]
+fn apply_row(predictor: Predictor, pixels: &[u8], width: usize, row: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(width);
+    for col in 0..width {
+        let a = if col > 0 { pixels[row * width + col - 1] } else { 0 };
+        let b = if row > 0 { pixels[(row - 1) * width + col] } else { 0 };
+        let c = if row > 0 && col > 0 {
+            pixels[(row - 1) * width + col - 1]
+        } else {
+            0
+        };
+        out.push(pixels[row * width + col].wrapping_sub(predictor.predict(a, b, c)));
+    }
+    out
+}
+
+/// Per-scanline counterpart of [`best_predictor`]: PNG-style, trial-applies every predictor to
+/// each row independently and keeps whichever minimizes that row's own zigzag-mapped residual
+/// magnitude, instead of picking one predictor for the whole plane. Returns one tag per row
+/// alongside the flat (row-major) residual stream; see [`invert_rows`] for the reverse.
+#[cfg(feature = "alloc")]
+pub fn best_predictor_rows(pixels: &[u8], width: usize) -> (Vec<Predictor>, Vec<u8>) {
+    let height = pixels.len() / width;
+    let mut tags = Vec::with_capacity(height);
+    let mut residuals = Vec::with_capacity(pixels.len());
+    for row in 0..height {
+        let (predictor, row_residuals) = ALL
+            .into_iter()
+            .map(|predictor| {
+                let residuals = apply_row(predictor, pixels, width, row);
+                let cost: usize = residuals
+                    .iter()
+                    .map(|&r| RICE_INDEX[r as usize] as usize)
+                    .sum();
+                (predictor, residuals, cost)
+            })
+            .min_by_key(|(_, _, cost)| *cost)
+            .map(|(predictor, residuals, _)| (predictor, residuals))
+            .expect("ALL is non-empty");
+        tags.push(predictor);
+        residuals.extend(row_residuals);
+    }
+    (tags, residuals)
+}
+
+/// Inverts [`best_predictor_rows`], reconstructing the original pixels from `residuals` using
+/// each row's own predictor tag (`tags[row]`).
+#[cfg(feature = "alloc")]
+pub fn invert_rows(tags: &[Predictor], residuals: &[u8], width: usize) -> Vec<u8> {
+    let mut out = alloc::vec![0u8; residuals.len()];
+    for (row, &predictor) in tags.iter().enumerate() {
+        for col in 0..width {
+            let index = row * width + col;
+            let a = if col > 0 { out[index - 1] } else { 0 };
+            let b = if row > 0 { out[index - width] } else { 0 };
+            let c = if row > 0 && col > 0 { out[index - width - 1] } else { 0 };
+            out[index] = residuals[index].wrapping_add(predictor.predict(a, b, c));
+        }
+    }
+    out
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_predictor_roundtrip() {
+    let width = 8;
+    let pixels: Vec<u8> = (0..64).map(|i: u32| (i * 7 % 251) as u8).collect();
+
+    for predictor in ALL {
+        let residuals = apply(predictor, &pixels, width);
+        let reconstructed = invert(predictor, &residuals, width);
+        assert_eq!(reconstructed, pixels, "{predictor:?} failed to round-trip");
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_best_predictor_rows_roundtrip() {
+    let width = 8;
+    let pixels: Vec<u8> = (0..64).map(|i: u32| (i * 37 % 251) as u8).collect();
+
+    let (tags, residuals) = best_predictor_rows(&pixels, width);
+    let reconstructed = invert_rows(&tags, &residuals, width);
+    assert_eq!(reconstructed, pixels);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_best_predictor_rows_picks_different_predictors_per_row() {
+    // Row 0 is a horizontal gradient (favors Left); row 1 repeats row 0 exactly (favors Up).
+    let width = 16;
+    let mut pixels: Vec<u8> = (0..width).map(|i| i as u8).collect();
+    pixels.extend(pixels.clone());
+
+    let (tags, _) = best_predictor_rows(&pixels, width);
+    assert_eq!(tags[0], Predictor::Left);
+    assert_eq!(tags[1], Predictor::Up);
+}
+
+#[test]
+fn test_med_picks_min_max_or_gradient() {
+    // c is the largest neighbour -> min(a, b)
+    assert_eq!(med(10, 20, 30), 10);
+    // c is the smallest neighbour -> max(a, b)
+    assert_eq!(med(10, 20, 5), 20);
+    // c is between a and b -> the planar gradient a + b - c
+    assert_eq!(med(10, 20, 15), 15);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_best_predictor_picks_left_for_horizontal_gradient() {
+    let width = 16;
+    // Each row is the same 0..16 ramp, shifted up by 100 on odd rows so the rows above don't
+    // predict it (identical rows would let Paeth/MED reach zero residuals and beat Left).
+    let pixels: Vec<u8> = (0..width * 4)
+        .map(|i| (i % width + 100 * (i / width % 2)) as u8)
+        .collect();
+    let (predictor, _) = best_predictor(&pixels, width);
+    assert_eq!(predictor, Predictor::Left);
+}
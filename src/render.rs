@@ -0,0 +1,100 @@
+//! Turns a decoded grayscale image into terminal preview text using ANSI truecolor half-block
+//! characters - the same trick rqrcode's `as_ansi` output uses for QR codes. One character
+//! cell packs two vertical pixels: the foreground color paints the top half, the background
+//! color the bottom half, so a preview keeps twice the vertical resolution a naive
+//! one-pixel-per-cell renderer would manage.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::fmt::Write as _;
+
+/// Upper half-block glyph: the foreground color fills its top half, the background color its
+/// bottom half.
+const UPPER_HALF_BLOCK: char = '\u{2580}';
+
+/// Renders `pixels` (row-major grayscale, `width` wide) as ANSI truecolor half-block text,
+/// one line per two image rows. If `max_width` is given and smaller than `width`, the image is
+/// nearest-neighbour downscaled (preserving aspect ratio) to fit it first.
+#[cfg(feature = "alloc")]
+pub fn render_ansi(pixels: &[u8], width: usize, max_width: Option<usize>) -> String {
+    if width == 0 || pixels.is_empty() {
+        return String::new();
+    }
+    let height = pixels.len() / width;
+
+    let (pixels, width, height) = match max_width {
+        Some(max_width) if max_width > 0 && max_width < width => {
+            let scaled_height = ((height * max_width) / width).max(1);
+            let scaled = downscale(pixels, width, height, max_width, scaled_height);
+            (scaled, max_width, scaled_height)
+        }
+        _ => (pixels.to_vec(), width, height),
+    };
+
+    // One output line per pair of image rows, plus escape codes - a rough but cheap estimate.
+    let mut out = String::with_capacity(pixels.len() * 20);
+    let row_pairs = (height + 1) / 2;
+    for pair in 0..row_pairs {
+        let top = pair * 2;
+        let bottom = top + 1;
+        for col in 0..width {
+            let fg = pixels[top * width + col];
+            let bg = if bottom < height {
+                pixels[bottom * width + col]
+            } else {
+                fg
+            };
+            let _ = write!(
+                out,
+                "\u{1b}[38;2;{fg};{fg};{fg}m\u{1b}[48;2;{bg};{bg};{bg}m{UPPER_HALF_BLOCK}"
+            );
+        }
+        out.push_str("\u{1b}[0m\n");
+    }
+    out
+}
+
+/// Nearest-neighbour downscales `pixels` from `width x height` to `out_width x out_height`,
+/// using only integer arithmetic so this stays usable without floating-point support.
+#[cfg(feature = "alloc")]
+fn downscale(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    out_width: usize,
+    out_height: usize,
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(out_width * out_height);
+    for out_y in 0..out_height {
+        let src_y = (out_y * height) / out_height;
+        for out_x in 0..out_width {
+            let src_x = (out_x * width) / out_width;
+            out.push(pixels[src_y * width + src_x]);
+        }
+    }
+    out
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_render_ansi_packs_two_rows_per_line() {
+    let width = 4;
+    let pixels = [0u8, 64, 128, 255, 10, 20, 30, 40];
+    let rendered = render_ansi(&pixels, width, None);
+    assert_eq!(rendered.lines().count(), 1);
+    assert_eq!(rendered.matches(UPPER_HALF_BLOCK).count(), width);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_render_ansi_downscales_to_max_width() {
+    let width = 8;
+    let pixels: Vec<u8> = (0..width * 4).map(|i| i as u8).collect();
+    let rendered = render_ansi(&pixels, width, Some(4));
+    // height is downscaled from 4 to 2 rows, packed into 1 terminal line of 4 columns.
+    assert_eq!(rendered.lines().count(), 1);
+    assert_eq!(rendered.matches(UPPER_HALF_BLOCK).count(), 4);
+}
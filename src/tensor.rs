@@ -0,0 +1,145 @@
+//! Generalizes the row/width Rice+predictor codec in [`crate::roi`] to arrays of arbitrary
+//! rank - volumetric scans, hyperspectral cubes, boolean/label tensors - by carrying a shape
+//! vector instead of a fixed width/height. In row-major order the fastest-varying axis is the
+//! last entry of `shape`; [`encode_band`]/[`decode_band`] already operate on exactly that axis
+//! when treating a flat `&[u8]` as `(row_count, width)`, so encoding an n-dimensional array is
+//! just folding every axis but the last into the row count and reusing the existing band codec
+//! unchanged.
+//!
+//! Boolean/label tensors ([`ElementType::Bool`]) are the one element type handled specially:
+//! each element only carries one bit of information, so rows are bit-packed 8-to-a-byte (via
+//! [`U8_TO_ARRAY_BOOL`] on the unpacking side) before Rice coding, rather than spending a whole
+//! Rice-coded byte per element. Packing resets at each row boundary - like the predictors
+//! themselves - so a row whose width isn't a multiple of 8 simply leaves the last packed byte
+//! partially unused instead of bleeding bits into the next row.
+//!
+//! This is a library-only API: the `tgif` binary reads its input exclusively through the
+//! `image` crate (`to_luma8`/`to_luma16`/`to_rgb8`/`to_rgba8`), which has no 3+-dimensional
+//! source format to decode into an n-dimensional `shape` in the first place. A CLI surface for
+//! this would need its own input format (e.g. raw binary + shape, or something `.npy`-like),
+//! which is a new input pipeline, not a flag on the existing image-conversion one.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::constants::U8_TO_ARRAY_BOOL;
+use crate::error::TgifError;
+use crate::roi::{decode_band, encode_band};
+
+/// Element type carried alongside a tensor's shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementType {
+    /// One byte per element, Rice-coded directly.
+    U8,
+    /// One bit per element (0 or non-zero), bit-packed 8-to-a-byte before Rice coding.
+    Bool,
+}
+
+/// Number of elements described by `shape`.
+pub fn element_count(shape: &[u32]) -> u64 {
+    shape.iter().map(|&d| d as u64).product()
+}
+
+/// Length of the fastest-varying (last) axis - the unit [`encode_tensor`]/[`decode_tensor`]
+/// walk when predicting and Rice-coding.
+fn row_len(shape: &[u32]) -> usize {
+    *shape.last().unwrap_or(&0) as usize
+}
+
+/// Encodes an n-dimensional array of `elements` (row-major, shaped by `shape`) into a single
+/// predictor-tagged, CRC32-protected band.
+#[cfg(feature = "alloc")]
+pub fn encode_tensor(
+    elements: &[u8],
+    shape: &[u32],
+    element_type: ElementType,
+    rem_bits: u8,
+) -> Vec<u8> {
+    let width = row_len(shape);
+    match element_type {
+        ElementType::U8 => encode_band(elements, width, rem_bits),
+        ElementType::Bool => {
+            let packed = pack_bits_per_row(elements, width);
+            encode_band(&packed, packed_row_len(width), rem_bits)
+        }
+    }
+}
+
+/// Decodes a band produced by [`encode_tensor`] back into `shape`'s elements.
+#[cfg(feature = "alloc")]
+pub fn decode_tensor(
+    chunk: &[u8],
+    shape: &[u32],
+    element_type: ElementType,
+    rem_bits: u8,
+) -> Result<Vec<u8>, TgifError> {
+    let width = row_len(shape);
+    match element_type {
+        ElementType::U8 => decode_band(chunk, width, rem_bits, 0),
+        ElementType::Bool => {
+            let packed = decode_band(chunk, packed_row_len(width), rem_bits, 0)?;
+            Ok(unpack_bits_per_row(&packed, packed_row_len(width), width))
+        }
+    }
+}
+
+/// Bytes needed to hold one bit-packed row of `width` boolean elements.
+fn packed_row_len(width: usize) -> usize {
+    (width + 7) / 8
+}
+
+/// Packs `width`-wide rows of 0/non-zero elements into bits, MSB-first, restarting a fresh
+/// byte at the start of every row.
+#[cfg(feature = "alloc")]
+fn pack_bits_per_row(elements: &[u8], width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(elements.len() / 8 + elements.len() / width.max(1) + 1);
+    for row in elements.chunks(width) {
+        for byte in row.chunks(8) {
+            let mut packed = 0u8;
+            for (bit_index, &element) in byte.iter().enumerate() {
+                if element != 0 {
+                    packed |= 1 << (7 - bit_index);
+                }
+            }
+            out.push(packed);
+        }
+    }
+    out
+}
+
+/// Inverts [`pack_bits_per_row`], truncating each row's unpacked bits back down to `width`.
+#[cfg(feature = "alloc")]
+fn unpack_bits_per_row(packed: &[u8], packed_width: usize, width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity((packed.len() / packed_width.max(1)) * width);
+    for row in packed.chunks(packed_width) {
+        let mut bits = row.iter().flat_map(|&byte| U8_TO_ARRAY_BOOL[byte as usize]);
+        for _ in 0..width {
+            out.push(bits.next().unwrap_or(false) as u8);
+        }
+    }
+    out
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_tensor_u8_roundtrip_3d() {
+    // A 4x3x5 volume: 4 slices of 3x5, viewed as 12 rows of width 5.
+    let shape = [4u32, 3, 5];
+    let elements: Vec<u8> = (0..element_count(&shape)).map(|i| (i * 17 % 251) as u8).collect();
+
+    let encoded = encode_tensor(&elements, &shape, ElementType::U8, 2);
+    let decoded = decode_tensor(&encoded, &shape, ElementType::U8, 2).unwrap();
+    assert_eq!(decoded, elements);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_tensor_bool_roundtrip_with_partial_last_byte() {
+    // Width 13 isn't a multiple of 8, exercising the partially-filled last packed byte.
+    let shape = [5u32, 13];
+    let elements: Vec<u8> = (0..element_count(&shape)).map(|i| (i % 3 == 0) as u8).collect();
+
+    let encoded = encode_tensor(&elements, &shape, ElementType::Bool, 0);
+    let decoded = decode_tensor(&encoded, &shape, ElementType::Bool, 0).unwrap();
+    assert_eq!(decoded, elements);
+}
@@ -3,51 +3,293 @@ use ndarray::Axis;
 use nshare::ToNdarray2;
 use std::io::Write;
 
+use tgif::color_transform::to_ycocg_r;
+use tgif::constants::{
+    rice_index_16, ADAPTIVE_REM_BITS, RICE_INDEX, RICE_PARTITION_REM_BITS, RLE_RUN_THRESHOLD,
+};
+use tgif::crc32::crc32;
+use tgif::error::TgifError;
+use tgif::header::Header;
+use tgif::predictor::{self, Predictor};
+use tgif::rice_partition;
+
 use crate::args;
-use crate::constants::{POW_OF_TWO, RICE_INDEX};
-use crate::header::Header;
+use crate::bit_writer::BitWriter;
 
-pub fn run(args: &args::ToTGIF) {
+pub fn run(args: &args::ToTGIF) -> Result<(), TgifError> {
     info!("Converting {} to {}", args.src, args.dst);
-    debug!("Reading the image from disk and converting it into an 2D ndarray");
-    let image = image::open(&args.src)
-        .expect("Failed reading input file.")
-        .to_luma8() // Coercing into 8-bit grayscale image
-        .into_ndarray2();
+    debug!("Reading the image from disk");
+    let source = image::open(&args.src)?;
 
     debug!("Coding the original image with rice coding");
-    let mut img = encode(&image, args.rem_bits, args.chunk_size as usize);
+    let (
+        payload,
+        width,
+        height,
+        pixel_count,
+        bit_depth,
+        rle,
+        channels,
+        color_transform,
+        predictors,
+        rem_bits,
+        row_tags,
+    ) = match source.color() {
+        image::ColorType::L16 => {
+            if args.rle {
+                info!("The --rle flag is only supported for 8-bit images; ignoring it");
+            }
+            if args.adaptive {
+                info!("The --adaptive flag is only supported for 8-bit images; ignoring it");
+            }
+            if args.rice_partition {
+                info!("The --rice-partition flag is only supported for 8-bit images; ignoring it");
+            }
+            if args.per_scanline_predictor {
+                info!(
+                    "The --per-scanline-predictor flag is only supported for 8-bit images; ignoring it"
+                );
+            }
+            let image = source.to_luma16().into_ndarray2();
+            let bytes = encode_16(&image, args.rem_bits, args.chunk_size as usize);
+            (
+                bytes,
+                image.shape()[1] as u32,
+                image.shape()[0] as u32,
+                image.len(),
+                16u8,
+                false,
+                1u8,
+                false,
+                // 16-bit images always use the plain left-neighbour delta.
+                [Predictor::Left.tag(), 0, 0, 0],
+                args.rem_bits,
+                Vec::new(),
+            )
+        }
+        image::ColorType::Rgb8 | image::ColorType::Rgba8 => {
+            let channel_count = if source.color() == image::ColorType::Rgba8 { 4 } else { 3 };
+            let width = source.width() as usize;
+            let height = source.height() as usize;
+            let interleaved: Vec<u8> = if channel_count == 4 {
+                source.to_rgba8().into_raw()
+            } else {
+                source.to_rgb8().into_raw()
+            };
+
+            debug!("Splitting the image into {channel_count} independently predicted planes");
+            let planes = planarize(&interleaved, channel_count, args.color_transform);
+
+            let mut predictors = [0u8; 4];
+            let mut residuals: Vec<u8> = Vec::with_capacity(interleaved.len());
+            let mut row_tags: Vec<u8> = Vec::new();
+            for (channel, plane) in planes.iter().enumerate() {
+                if args.per_scanline_predictor {
+                    let (tags, plane_residuals) = predictor::best_predictor_rows(plane, width);
+                    predictors[channel] = predictor::PER_SCANLINE_TAG;
+                    row_tags.extend(tags.iter().map(|p| p.tag()));
+                    residuals.extend(plane_residuals);
+                } else {
+                    let (predictor, plane_residuals) = predictor::best_predictor(plane, width);
+                    trace!("Channel {channel}: chose {predictor:?}");
+                    predictors[channel] = predictor.tag();
+                    residuals.extend(plane_residuals);
+                }
+            }
+
+            let (bytes, rem_bits, rle) = encode_residuals(&residuals, width, args);
+            (
+                bytes,
+                width as u32,
+                height as u32,
+                interleaved.len(),
+                8u8,
+                rle,
+                channel_count as u8,
+                args.color_transform,
+                predictors,
+                rem_bits,
+                row_tags,
+            )
+        }
+        _ => {
+            let image = source.to_luma8().into_ndarray2();
+            let width = image.shape()[1];
+            let pixels = image.as_slice().expect("image should be in standard layout");
+
+            let (predictor_tag, residuals, row_tags) = if args.per_scanline_predictor {
+                let (tags, residuals) = predictor::best_predictor_rows(pixels, width);
+                (
+                    predictor::PER_SCANLINE_TAG,
+                    residuals,
+                    tags.iter().map(|p| p.tag()).collect(),
+                )
+            } else {
+                debug!(
+                    "Picking the spatial predictor that minimizes the estimated Rice-coded size"
+                );
+                let (predictor, residuals) = predictor::best_predictor(pixels, width);
+                trace!("Chose {predictor:?}");
+                (predictor.tag(), residuals, Vec::new())
+            };
 
-    trace!("Padding the end with '1'");
-    img.extend(vec![true; 8 - (image.len() % 8)]);
+            let (bytes, rem_bits, rle) = encode_residuals(&residuals, width, args);
+            (
+                bytes,
+                width as u32,
+                image.shape()[0] as u32,
+                image.len(),
+                8u8,
+                rle,
+                1u8,
+                false,
+                [predictor_tag, 0, 0, 0],
+                rem_bits,
+                row_tags,
+            )
+        }
+    };
 
     trace!("Creating the header of the compressed image");
     let header = Header::new(
-        image.shape()[1] as u32,
-        image.shape()[0] as u32,
+        width,
+        height,
         args.chunk_size,
-        args.rem_bits,
+        rem_bits,
+        bit_depth,
+        rle,
+        channels,
+        color_transform,
+        predictors,
     )
     .to_u8();
 
-    trace!("Combining header with the compressed image");
-    let img = header
-        .into_iter()
-        .chain(img.chunks_exact(8).map(|chunk|
-                // Creates an u8 from [bool; 8]
-                chunk.iter().fold(0u8, |a, b| (a << 1) + *b as u8)))
-        .collect::<Vec<u8>>();
+    trace!("Prepending a CRC32 to each self contained chunk");
+    let chunk_bytes = (args.chunk_size / 8) as usize;
+    let mut img = header;
+    // The per-scanline predictor tags (if any) are a small uncompressed section between the
+    // header and the CRC-chunked payload, itself prefixed with its own CRC32 so a corrupt tag
+    // byte is caught instead of silently picking the wrong predictor - see
+    // `from_tgif::split_row_tags` for the reader.
+    if !row_tags.is_empty() {
+        img.extend(crc32(&row_tags).to_be_bytes());
+    }
+    img.extend_from_slice(&row_tags);
+    for chunk in payload.chunks(chunk_bytes) {
+        img.extend(crc32(chunk).to_be_bytes());
+        img.extend_from_slice(chunk);
+    }
 
     debug!("Writing the TGIF image to disk: {}", args.dst);
-    let mut file = std::fs::File::create(&args.dst).expect("Failed creating destination file");
-    file.write_all(&img)
-        .expect("Failed writing the image to disk");
+    let mut file = std::fs::File::create(&args.dst)?;
+    file.write_all(&img)?;
 
-    let rate = img.len() as f64 / image.len() as f64 * 100.0;
-    info!("Finished! Achieved compression rate of {rate:.4} %")
+    let rate = img.len() as f64 / pixel_count as f64 * 100.0;
+    info!("Finished! Achieved compression rate of {rate:.4} %");
+    Ok(())
+}
+
+/// Rice-codes `residuals` per the CLI's chosen mode (`--rice-partition` takes priority over
+/// `--adaptive`, which takes priority over `--rle`), returning the encoded bytes alongside the
+/// `rem_bits`/`rle` values to record in the header. Shared by the single-channel luma8 and
+/// multi-channel RGB(A) paths in [`run`].
+fn encode_residuals(residuals: &[u8], width: usize, args: &args::ToTGIF) -> (Vec<u8>, u8, bool) {
+    if args.rice_partition {
+        if args.adaptive {
+            info!(
+                "The --adaptive flag is not supported together with --rice-partition; ignoring it"
+            );
+        }
+        if args.rle {
+            info!("The --rle flag is not supported together with --rice-partition; ignoring it");
+        }
+        (
+            encode_rice_partitioned(residuals),
+            RICE_PARTITION_REM_BITS,
+            false,
+        )
+    } else if args.adaptive {
+        if args.rle {
+            info!("The --rle flag is not supported together with --adaptive; ignoring it");
+        }
+        (
+            encode_adaptive(residuals, args.chunk_size as usize),
+            ADAPTIVE_REM_BITS,
+            false,
+        )
+    } else if args.rle {
+        (
+            encode_rle(residuals, width, args.rem_bits, args.chunk_size as usize),
+            args.rem_bits,
+            true,
+        )
+    } else {
+        (
+            encode(residuals, args.rem_bits, args.chunk_size as usize),
+            args.rem_bits,
+            false,
+        )
+    }
+}
+
+/// Largest partition order `encode_rice_partitioned` will consider - `2^6 = 64` partitions,
+/// matching `rice_partition`'s own test coverage.
+const MAX_PARTITION_ORDER: u32 = 6;
+
+/// FLAC-style counterpart of [`encode`]: rice-codes the whole plane as a single
+/// [`crate::rice_partition`]-partitioned block (its own per-partition `k`s prefixed ahead of
+/// the bitstream) instead of splitting it into independently-padded `CHUNK_SIZE` blocks. This
+/// trades `from_tgif`'s parallel per-chunk decode for finer-grained Rice parameters; selected
+/// via [`RICE_PARTITION_REM_BITS`] instead of a real `rem_bits`.
+fn encode_rice_partitioned(residuals: &[u8]) -> Vec<u8> {
+    let rice_ind: Vec<u8> = residuals.iter().map(|&r| RICE_INDEX[r as usize]).collect();
+    let bits = rice_partition::encode_partitioned(&rice_ind, MAX_PARTITION_ORDER);
+    pack_bits(&bits)
+}
+
+/// Packs a bit vector (as produced by [`tgif::rice_partition::encode_partitioned`]) into bytes,
+/// MSB-first per byte, padding the final byte with zero bits - the write-side mirror of how
+/// `tgif::constants::U8_TO_ARRAY_BOOL` unpacks bytes back into bits on decode.
+fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|byte_bits| {
+            byte_bits
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, &bit)| byte | ((bit as u8) << (7 - i)))
+        })
+        .collect()
+}
+
+/// Splits an interleaved RGB(A) buffer (`channels`-wide per pixel) into one plane per channel,
+/// each `width * height` bytes in row-major order - ready for [`tgif::predictor::best_predictor`]
+/// to predict independently, same as a plain grayscale image. When `color_transform` is set, the
+/// first three channels are first run through the reversible YCoCg-R transform to decorrelate
+/// them; a 4th (alpha) channel, if present, is never transformed.
+fn planarize(pixels: &[u8], channels: usize, color_transform: bool) -> Vec<Vec<u8>> {
+    let pixel_count = pixels.len() / channels;
+    let mut planes: Vec<Vec<u8>> =
+        (0..channels).map(|_| Vec::with_capacity(pixel_count)).collect();
+
+    for pixel in pixels.chunks_exact(channels) {
+        let (r, g, b) = if color_transform {
+            to_ycocg_r(pixel[0], pixel[1], pixel[2])
+        } else {
+            (pixel[0], pixel[1], pixel[2])
+        };
+        planes[0].push(r);
+        planes[1].push(g);
+        planes[2].push(b);
+        if channels == 4 {
+            planes[3].push(pixel[3]);
+        }
+    }
+    planes
 }
 
-fn encode(image: &ndarray::Array2<u8>, rem_bits: u8, chunk_size: usize) -> Vec<bool> {
+/// Rice-codes pre-predicted `residuals` (see [`tgif::predictor::best_predictor`] and
+/// [`tgif::predictor::best_predictor_rows`], applied per plane in [`run`]).
+fn encode(residuals: &[u8], rem_bits: u8, chunk_size: usize) -> Vec<u8> {
     assert!(
         rem_bits <= 7,
         "No compression is possible with 8 or more remainder bits"
@@ -56,40 +298,281 @@ fn encode(image: &ndarray::Array2<u8>, rem_bits: u8, chunk_size: usize) -> Vec<b
     // The remainder is smaller than this number remainder < rem_max (∀ remainder)
     let rem_max = 2_u8.pow(rem_bits as u32);
 
-    // Stores the encoded image as a vector of bool
     // Capacity is estimated for no compression to prevent reallocation
-    let image_size = image.len() * 8; // Number of bits in the image
-    let mut img: Vec<bool> = Vec::with_capacity(image_size);
-
-    // Counter that keeps tracks of how many bits are in the current chunk
-    let mut chunk: usize = 0;
+    let image_size = residuals.len() * 8; // Number of bits in the image
+    let mut img = BitWriter::with_capacity(image_size / 8);
 
     // Counter that keeps track of how many bits are being used on padding
     let mut padding: usize = 0;
 
-    // Iterating over the image
-    debug!("Encoding the image as Vec<bool>");
+    debug!("Encoding the image as packed bytes");
+    for &residual in residuals {
+        let rice = RICE_INDEX[residual as usize]; // Determines the rice index
+        let quotient = rice / rem_max;
+        let remainder = rice % rem_max;
+        let bits = quotient as usize + 1 + rem_bits as usize;
+
+        // Bit-padding in case this would overstep the predetermined CHUNK_SIZE
+        if img.bit_len() % chunk_size + bits > chunk_size {
+            padding += img.align_to(chunk_size);
+        }
+
+        unary_coding(&mut img, quotient); // Unary coding of the quotient
+        remainder_coding(&mut img, remainder, rem_bits); // Binary coding of the rem
+    }
+
+    debug!(
+        "Used {:.2} % Bits for padding: {}",
+        100.0 * (padding as f64 / image_size as f64),
+        padding
+    );
+    img.finish()
+}
+
+/// Width, in bits, of the fixed-size header that precedes every adaptive block's codes - wide
+/// enough for any `k` in `0..8` (see [`encode_adaptive`]).
+const K_HEADER_BITS: u32 = 3;
+
+/// Adaptive counterpart of [`encode`]: instead of the single, globally-fixed `rem_bits`, each
+/// `CHUNK_SIZE` block picks its own Rice parameter `k` to fit local texture, chosen to minimize
+/// `sum(index >> k) + N * (1 + k)` (the block's encoded size at parameter `k`) and recorded as
+/// a [`K_HEADER_BITS`]-wide field at the start of the block. See
+/// [`tgif::codec::decode_adaptive`] for the matching reader. Wired up via `--adaptive`.
+fn encode_adaptive(residuals: &[u8], chunk_size: usize) -> Vec<u8> {
+    let image_size = residuals.len() * 8;
+    let mut img = BitWriter::with_capacity(image_size / 8);
+
+    let mut pending: Vec<u8> = Vec::new();
+    // cost_by_k[k] = sum(index >> k) over `pending`, kept running so every candidate `k` can be
+    // cheaply re-evaluated as indices are buffered.
+    let mut cost_by_k = [0u64; 8];
+    let mut padding: usize = 0;
+
+    for &residual in residuals {
+        let rice = RICE_INDEX[residual as usize];
+
+        let mut trial_cost = cost_by_k;
+        for (k, cost) in trial_cost.iter_mut().enumerate() {
+            *cost += (rice >> k) as u64;
+        }
+        let (_, trial_bits) = best_k(&trial_cost, pending.len() as u64 + 1);
+
+        if !pending.is_empty() && K_HEADER_BITS as u64 + trial_bits > chunk_size as u64 {
+            padding += flush_adaptive_block(&mut img, &pending, &cost_by_k, chunk_size);
+            pending.clear();
+            cost_by_k = [0u64; 8];
+            for (k, cost) in cost_by_k.iter_mut().enumerate() {
+                *cost = (rice >> k) as u64;
+            }
+        } else {
+            cost_by_k = trial_cost;
+        }
+        pending.push(rice);
+    }
+    if !pending.is_empty() {
+        padding += flush_adaptive_block(&mut img, &pending, &cost_by_k, chunk_size);
+    }
+
+    debug!(
+        "Used {:.2} % Bits for padding: {}",
+        100.0 * (padding as f64 / image_size as f64),
+        padding
+    );
+    img.finish()
+}
+
+/// Picks the `k` in `0..8` minimizing `sum(index >> k) + n * (1 + k)` given the running
+/// per-`k` sums in `cost_by_k`, returning that `k` alongside the resulting total bits.
+fn best_k(cost_by_k: &[u64; 8], n: u64) -> (u8, u64) {
+    (0u8..8)
+        .map(|k| (k, cost_by_k[k as usize] + n * (1 + k as u64)))
+        .min_by_key(|&(_, bits)| bits)
+        .expect("k ranges over a fixed non-empty 0..8")
+}
+
+/// Emits one adaptive block: a [`K_HEADER_BITS`]-wide `k` header followed by `pending`'s Rice
+/// codes at that `k`, then pads to the next chunk boundary so the block stays self-contained
+/// for parallel decode. Returns the number of padding bits used.
+fn flush_adaptive_block(
+    img: &mut BitWriter,
+    pending: &[u8],
+    cost_by_k: &[u64; 8],
+    chunk_size: usize,
+) -> usize {
+    let (k, _) = best_k(cost_by_k, pending.len() as u64);
+    let rem_max = 2u8.pow(k as u32);
+
+    img.put_bits(k as u64, K_HEADER_BITS);
+    for &rice in pending {
+        unary_coding(img, rice / rem_max);
+        remainder_coding(img, rice % rem_max, k);
+    }
+    img.align_to(chunk_size)
+}
+
+/// Hybrid run-length/rice counterpart of [`encode`]. Within each chunk, runs of `>=
+/// RLE_RUN_THRESHOLD` identical consecutive rice indices are coded as a single *run* token
+/// (a varint length plus one rice-coded value) instead of one rice code per pixel, which is
+/// much cheaper for the flat regions common in synthetic images. Shorter stretches fall back
+/// to a *literal group* token (a varint count plus that many ordinary rice codes). A leading
+/// bit distinguishes the two token kinds, and - like individual pixels in [`encode`] - a
+/// token is never split across a chunk boundary.
+fn encode_rle(residuals: &[u8], width: usize, rem_bits: u8, chunk_size: usize) -> Vec<u8> {
+    assert!(
+        rem_bits <= 7,
+        "No compression is possible with 8 or more remainder bits"
+    );
+
+    let rem_max = 2_u8.pow(rem_bits as u32);
+    let image_size = residuals.len() * 8;
+    let mut img = BitWriter::with_capacity(image_size / 8);
+
+    let mut padding: usize = 0;
+
+    debug!("Encoding the image as packed bytes using the hybrid RLE/rice scheme");
+    for residual_row in residuals.chunks_exact(width) {
+        // First pass: map the already-predicted row onto its rice indices
+        let row_rice: Vec<u8> = residual_row
+            .iter()
+            .map(|&residual| RICE_INDEX[residual as usize])
+            .collect();
+
+        // Second pass: tokenize the row into runs and literal groups
+        let mut i = 0;
+        while i < row_rice.len() {
+            let run_len = run_length(&row_rice[i..]);
+            if run_len >= RLE_RUN_THRESHOLD {
+                let bits = 1
+                    + varint_bits(run_len as u64)
+                    + rice_bits(row_rice[i], rem_max, rem_bits);
+                pad_to_chunk_boundary(&mut img, &mut padding, bits, chunk_size);
+
+                img.put_bits(1, 1); // Run token
+                varint_encode(&mut img, run_len as u64);
+                rice_code(&mut img, row_rice[i], rem_max, rem_bits);
+                i += run_len;
+            } else {
+                let start = i;
+                let mut j = i;
+                while j < row_rice.len() && run_length(&row_rice[j..]) < RLE_RUN_THRESHOLD {
+                    j += 1;
+                }
+                let literal = &row_rice[start..j];
+                let bits = 1
+                    + varint_bits(literal.len() as u64)
+                    + literal
+                        .iter()
+                        .map(|&ind| rice_bits(ind, rem_max, rem_bits))
+                        .sum::<usize>();
+                pad_to_chunk_boundary(&mut img, &mut padding, bits, chunk_size);
+
+                img.put_bits(0, 1); // Literal-group token
+                varint_encode(&mut img, literal.len() as u64);
+                for &ind in literal {
+                    rice_code(&mut img, ind, rem_max, rem_bits);
+                }
+                i = j;
+            }
+        }
+    }
+
+    debug!(
+        "Used {:.2} % Bits for padding: {}",
+        100.0 * (padding as f64 / image_size as f64),
+        padding
+    );
+    img.finish()
+}
+
+/// Number of leading elements of `rice` equal to `rice[0]`.
+fn run_length(rice: &[u8]) -> usize {
+    match rice.first() {
+        Some(&first) => rice.iter().take_while(|&&v| v == first).count(),
+        None => 0,
+    }
+}
+
+/// Pads `img` to the next chunk boundary with "1"s if the upcoming `bits`-sized token would
+/// otherwise straddle it, keeping every chunk self-contained for parallel decode.
+fn pad_to_chunk_boundary(img: &mut BitWriter, padding: &mut usize, bits: usize, chunk_size: usize) {
+    if img.bit_len() % chunk_size + bits > chunk_size {
+        *padding += img.align_to(chunk_size);
+    }
+}
+
+/// Rice-codes a single already-computed index (quotient in unary, remainder in binary).
+fn rice_code(img: &mut BitWriter, rice: u8, rem_max: u8, rem_bits: u8) {
+    unary_coding(img, rice / rem_max);
+    remainder_coding(img, rice % rem_max, rem_bits);
+}
+
+/// Number of bits a single rice-coded index takes up: the unary quotient, its terminating
+/// "0", and the fixed-width remainder.
+fn rice_bits(rice: u8, rem_max: u8, rem_bits: u8) -> usize {
+    (rice / rem_max) as usize + 1 + rem_bits as usize
+}
+
+/// LEB128-style varint: 7 value bits per byte, MSB-first, with a leading continuation bit.
+fn varint_encode(img: &mut BitWriter, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let more = value != 0;
+        img.put_bits(more as u64, 1);
+        img.put_bits(byte as u64, 7);
+        if !more {
+            break;
+        }
+    }
+}
+
+/// Number of bits [`varint_encode`] would use for `value`.
+fn varint_bits(value: u64) -> usize {
+    let mut v = value;
+    let mut groups = 1;
+    loop {
+        v >>= 7;
+        if v == 0 {
+            break;
+        }
+        groups += 1;
+    }
+    groups * 8
+}
+
+/// 16-bit counterpart of [`encode`], used for `L16` sources. Shares the same chunking and
+/// bit-level coding scheme, just folding deltas through [`rice_index_16`] instead of the
+/// 8-bit lookup table.
+fn encode_16(image: &ndarray::Array2<u16>, rem_bits: u8, chunk_size: usize) -> Vec<u8> {
+    assert!(
+        rem_bits <= 7,
+        "No compression is possible with 8 or more remainder bits"
+    );
+
+    let rem_max = 2_u16.pow(rem_bits as u32);
+
+    let image_size = image.len() * 16;
+    let mut img = BitWriter::with_capacity(image_size / 8);
+
+    let mut padding: usize = 0;
+
+    debug!("Encoding the 16-bit image as packed bytes");
     for axis in image.axis_iter(Axis(0)) {
-        let mut prev: u8 = 0; // All pixel outside of the image are defined as 0
+        let mut prev: u16 = 0; // All pixel outside of the image are defined as 0
         for pixel in axis {
             let delta = prev.wrapping_sub(*pixel); // Calc the delta
-            let rice = RICE_INDEX[delta as usize]; // Determines the rice index
+            let rice = rice_index_16(delta); // Determines the rice index
             let quotient = rice / rem_max;
             let remainder = rice % rem_max;
             let bits = quotient as usize + 1 + rem_bits as usize;
 
-            // Bit-padding in case this would overstep the predetermined CHUNK_SIZE
-            if chunk + bits > chunk_size {
-                //
-                padding += chunk_size - chunk;
-                img.extend(vec![true; chunk_size - chunk]);
-                chunk = 0;
+            if img.bit_len() % chunk_size + bits > chunk_size {
+                padding += img.align_to(chunk_size);
             }
 
-            chunk += bits;
-            prev = *pixel; // Updating the previous pixel
-            unary_coding(&mut img, quotient); // Unary coding of the quotient
-            remainder_coding(&mut img, remainder, rem_bits); // Binary coding of the rem
+            prev = *pixel;
+            unary_coding_16(&mut img, quotient);
+            remainder_coding_16(&mut img, remainder, rem_bits);
         }
     }
 
@@ -98,22 +581,28 @@ fn encode(image: &ndarray::Array2<u8>, rem_bits: u8, chunk_size: usize) -> Vec<b
         100.0 * (padding as f64 / image_size as f64),
         padding
     );
-    img
+    img.finish()
 }
 
 /// Codes the remainder as boolean binary with `remainder_bits` bit-width
-fn remainder_coding(img: &mut Vec<bool>, rem: u8, rem_bits: u8) {
+fn remainder_coding(img: &mut BitWriter, rem: u8, rem_bits: u8) {
     debug_assert!(rem_bits <= 8); // Hoping for better optimization
     debug_assert!(rem < 2u8.pow(rem_bits as u32));
-    img.extend(
-        (0..rem_bits)
-            .rev() // <-> Most significant bit
-            .map(|ind| rem & POW_OF_TWO[ind as usize] != 0),
-    )
+    img.put_bits(rem as u64, rem_bits as u32)
 }
 
 /// Unary coding of the quotient
-fn unary_coding(img: &mut Vec<bool>, quot: u8) {
-    img.extend(vec![true; quot as usize]);
-    img.push(false);
+fn unary_coding(img: &mut BitWriter, quot: u8) {
+    img.put_unary(quot as u32);
+}
+
+/// 16-bit counterpart of [`remainder_coding`]
+fn remainder_coding_16(img: &mut BitWriter, rem: u16, rem_bits: u8) {
+    debug_assert!(rem < 2u16.pow(rem_bits as u32));
+    img.put_bits(rem as u64, rem_bits as u32)
+}
+
+/// 16-bit counterpart of [`unary_coding`]
+fn unary_coding_16(img: &mut BitWriter, quot: u16) {
+    img.put_unary(quot as u32);
 }
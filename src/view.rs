@@ -0,0 +1,53 @@
+//! Implements `tgif view`: renders a TGIF file directly in the terminal instead of converting
+//! it, reusing the same decode path as [`crate::from_tgif`] and handing the pixels off to
+//! [`tgif::render::render_ansi`].
+
+use log::{debug, info};
+
+use tgif::error::TgifError;
+use tgif::header::Header;
+use tgif::render::render_ansi;
+
+use crate::args::View;
+use crate::from_tgif::{decode, decode_16, decode_planes, split_row_tags};
+
+pub fn run(args: &View) -> Result<(), TgifError> {
+    info!("Rendering a preview of {}", args.path);
+
+    debug!("Reading the TGIF file from disk");
+    let tgif = std::fs::read(&args.path)?;
+
+    debug!("Parsing the header");
+    let header = Header::from_u8(&tgif)?;
+    let (row_tags, payload) = split_row_tags(&tgif, &header)?;
+
+    // Never tolerate silently-missing rows in a preview - a dropped chunk would otherwise
+    // shift every row below it.
+    let pixels: Vec<u8> = match header.bit_depth {
+        16 => decode_16(payload, &header, false)?
+            .into_iter()
+            .map(|pixel| (pixel >> 8) as u8)
+            .collect(),
+        _ if header.channels > 1 => {
+            // `render_ansi` only understands single-channel grayscale, so collapse the
+            // decoded RGB(A) pixels down to luma (BT.601) for the preview; the alpha channel,
+            // if present, is dropped here the same way `image::to_luma8` would drop it.
+            let channels = header.channels as usize;
+            decode_planes(payload, &header, row_tags, false)?
+                .chunks_exact(channels)
+                .map(|pixel| {
+                    let luma = 0.299 * pixel[0] as f32
+                        + 0.587 * pixel[1] as f32
+                        + 0.114 * pixel[2] as f32;
+                    luma.round() as u8
+                })
+                .collect()
+        }
+        _ => decode(payload, &header, row_tags, false)?,
+    };
+
+    let max_width = args.width.map(|width| width as usize);
+    print!("{}", render_ansi(&pixels, header.width as usize, max_width));
+
+    Ok(())
+}